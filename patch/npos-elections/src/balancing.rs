@@ -26,9 +26,13 @@
 //!
 //! See [`balance`] for more information.
 
-use crate::{BalancingConfig, Edge, ExtendedBalance, IdentifierT, Voter};
+use crate::{
+	BalancingConfig, BalancingIterationUpdate, Edge, EdgeWeightUpdate, ElectionScore,
+	ElectionScoreUpdate, EvaluateSupport, ExtendedBalance, IdentifierT, PhragmenTrace, Support,
+	Supports, Voter,
+};
 use sp_arithmetic::traits::Zero;
-use sp_std::prelude::*;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
 /// Balance the weight distribution of a given `voters` at most `iterations` times, or up until the
 /// point where the biggest difference created per iteration of all stakes is `tolerance`. If this
@@ -59,25 +63,82 @@ pub fn balance<AccountId: IdentifierT>(
 	voters: &mut Vec<Voter<AccountId>>,
 	config: &BalancingConfig,
 ) -> usize {
-	if config.iterations == 0 {
-		return 0
-	}
+	balance_with_tracing(voters, config).0
+}
+
+/// Same as [`balance`], but also returns the trace of all balancing iterations.
+///
+/// Every iteration records the edge weights it changed and the [`ElectionScore`] it resulted in.
+/// The trace is closed with the change of score caused by the whole balancing process.
+pub fn balance_with_tracing<AccountId: IdentifierT>(
+	voters: &mut Vec<Voter<AccountId>>,
+	config: &BalancingConfig,
+) -> (usize, Vec<PhragmenTrace<AccountId>>) {
+	// tracing: BalancingStart
+	let mut tracing: Vec<PhragmenTrace<AccountId>> = vec![PhragmenTrace::BalancingStart];
+	let initial_score = voters_score(voters);
+
+	let iterations = if config.iterations == 0 {
+		0
+	} else {
+		let mut iter = 0;
+		loop {
+			let mut max_diff = 0;
+			let mut edges = Vec::new();
+			for voter in voters.iter_mut() {
+				// edge weights before balancing, used only for tracing.
+				let weights = voter.edges.iter().map(|e| e.weight).collect::<Vec<_>>();
+
+				let diff = balance_voter(voter, config.tolerance);
+				if diff > max_diff {
+					max_diff = diff;
+				}
+
+				// balance_voter does not reorder the edges of a voter.
+				for (edge, weight) in voter.edges.iter().zip(weights.into_iter()) {
+					if edge.weight != weight {
+						edges.push(EdgeWeightUpdate { voter: voter.who.clone(), candidate: edge.who.clone(), weight, new_weight: edge.weight });
+					}
+				}
+			}
+
+			iter += 1;
+
+			// tracing: BalancingIteration
+			tracing.push(PhragmenTrace::BalancingIteration(BalancingIterationUpdate { iteration: iter, max_diff, edges, score: voters_score(voters) }));
 
-	let mut iter = 0;
-	loop {
-		let mut max_diff = 0;
-		for voter in voters.iter_mut() {
-			let diff = balance_voter(voter, config.tolerance);
-			if diff > max_diff {
-				max_diff = diff;
+			if max_diff <= config.tolerance || iter >= config.iterations {
+				break iter
 			}
 		}
+	};
+
+	// tracing: BalancingFinish
+	tracing.push(PhragmenTrace::BalancingFinish(iterations));
 
-		iter += 1;
-		if max_diff <= config.tolerance || iter >= config.iterations {
-			break iter
+	// tracing: ElectionScoreUpdated
+	tracing.push(PhragmenTrace::ElectionScoreUpdated(ElectionScoreUpdate { score: initial_score, new_score: voters_score(voters) }));
+
+	(iterations, tracing)
+}
+
+/// Compute the [`ElectionScore`] of the current edge weight distribution of `voters`.
+///
+/// Only edges pointing to elected candidates are taken into account.
+pub fn voters_score<AccountId: IdentifierT>(voters: &[Voter<AccountId>]) -> ElectionScore {
+	let mut totals = BTreeMap::<AccountId, ExtendedBalance>::new();
+	for voter in voters {
+		for edge in voter.edges.iter().filter(|e| e.candidate.borrow().elected) {
+			let total = totals.entry(edge.who.clone()).or_default();
+			*total = total.saturating_add(edge.weight);
 		}
 	}
+
+	totals
+		.into_iter()
+		.map(|(who, total)| (who, Support { total, voters: vec![] }))
+		.collect::<Supports<AccountId>>()
+		.evaluate()
 }
 
 /// Internal implementation of balancing for one voter.
@@ -76,12 +76,17 @@ pub fn seq_phragmen<AccountId: IdentifierT, P: PerThing128>(
 ) -> Result<(ElectionResult<AccountId, P>, Vec<CandidatePtr<AccountId>>, Vec<PhragmenTrace<AccountId>>), crate::Error> {
 	let (candidates, voters) = setup_inputs(candidates, voters);
 
-	let (candidates, mut voters, tracing) = seq_phragmen_core::<AccountId>(to_elect, candidates, voters)?;
+	let (candidates, mut voters, mut tracing) = seq_phragmen_core::<AccountId>(to_elect, candidates, voters)?;
 
 	if let Some(ref config) = balancing {
 		// NOTE: might create zero-edges, but we will strip them again when we convert voter into
 		// assignment.
-		let _iters = balancing::balance::<AccountId>(&mut voters, config);
+		let (_iters, balancing_tracing) = balancing::balance_with_tracing::<AccountId>(&mut voters, config);
+
+		// tracing: balancing goes right before Finish
+		let finish = tracing.pop();
+		tracing.extend(balancing_tracing);
+		tracing.extend(finish);
 	}
 
 	let mut winners = candidates.clone()
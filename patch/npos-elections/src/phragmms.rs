@@ -22,8 +22,9 @@
 //! MMS algorithm.
 
 use crate::{
-	balance, setup_inputs, BalancingConfig, CandidatePtr, ElectionResult, ExtendedBalance,
-	IdentifierT, PerThing128, VoteWeight, Voter,
+	balance_with_tracing, setup_inputs, BalancingConfig, CandidatePtr, ElectionResult, ExtendedBalance,
+	IdentifierT, PerThing128, VoteWeight, Voter, PhragmenTrace, CandidateScoreUpdate,
+	CandidateScoreUpdateByVoter, EdgeWeightUpdate,
 };
 use sp_arithmetic::{traits::Bounded, PerThing, Rational128};
 use sp_std::{prelude::*, rc::Rc};
@@ -47,23 +48,59 @@ pub fn phragmms<AccountId: IdentifierT, P: PerThing128>(
 	voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
 	balancing: Option<BalancingConfig>,
 ) -> Result<ElectionResult<AccountId, P>, crate::Error> {
+	phragmms_with_tracing(to_elect, candidates, voters, balancing).map(|(result, _, _)| result)
+}
+
+/// Execute the phragmms method, recording every internal step.
+///
+/// Same as [`phragmms`], but additionally returns all candidates (with their final state) and the
+/// trace of the election, in the same manner as [`crate::seq_phragmen`].
+pub fn phragmms_with_tracing<AccountId: IdentifierT, P: PerThing128>(
+	to_elect: usize,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+	balancing: Option<BalancingConfig>,
+) -> Result<(ElectionResult<AccountId, P>, Vec<CandidatePtr<AccountId>>, Vec<PhragmenTrace<AccountId>>), crate::Error> {
 	let (candidates, mut voters) = setup_inputs(candidates, voters);
 
+	// tracing: Start
+	let mut tracing: Vec<PhragmenTrace<AccountId>> = vec![PhragmenTrace::Start];
+
+	// tracing: ToElect
+	tracing.push(PhragmenTrace::ToElect(to_elect.min(candidates.len())));
+
 	let mut winners = vec![];
 	for round in 0..to_elect {
-		if let Some(round_winner) = calculate_max_score::<AccountId, P>(&candidates, &voters) {
-			apply_elected::<AccountId>(&mut voters, Rc::clone(&round_winner));
+		// tracing: RoundStart
+		tracing.push(PhragmenTrace::RoundStart(round, candidates.iter().map(|c_ptr| c_ptr.borrow().clone()).collect(), voters.clone()));
+
+		// tracing: ComputeMaxScores
+		tracing.push(PhragmenTrace::ComputeMaxScores);
+
+		if let Some(round_winner) = calculate_max_score::<AccountId, P>(&candidates, &voters, &mut tracing) {
+			// tracing: CandidateElected
+			tracing.push(PhragmenTrace::CandidateElected(round_winner.borrow().clone()));
+
+			// tracing: ApplyElected
+			tracing.push(PhragmenTrace::ApplyElected);
+
+			apply_elected::<AccountId>(&mut voters, Rc::clone(&round_winner), &mut tracing);
 
 			round_winner.borrow_mut().round = round;
 			round_winner.borrow_mut().elected = true;
 			winners.push(round_winner);
 
 			if let Some(ref config) = balancing {
-				balance(&mut voters, config);
+				// tracing: BalancingStart .. ElectionScoreUpdated
+				let (_iters, balancing_tracing) = balance_with_tracing(&mut voters, config);
+				tracing.extend(balancing_tracing);
 			}
 		} else {
 			break
 		}
+
+		// tracing: RoundEnd
+		tracing.push(PhragmenTrace::RoundEnd(round, candidates.iter().map(|c_ptr| c_ptr.borrow().clone()).collect(), voters.clone()));
 	}
 
 	let mut assignments =
@@ -77,7 +114,10 @@ pub fn phragmms<AccountId: IdentifierT, P: PerThing128>(
 		.map(|w_ptr| (w_ptr.borrow().who.clone(), w_ptr.borrow().backed_stake))
 		.collect();
 
-	Ok(ElectionResult { winners, assignments })
+	// tracing: Finish
+	tracing.push(PhragmenTrace::Finish);
+
+	Ok((ElectionResult { winners, assignments }, candidates, tracing))
 }
 
 /// Find the candidate that can yield the maximum score for this round.
@@ -91,7 +131,11 @@ pub fn phragmms<AccountId: IdentifierT, P: PerThing128>(
 pub(crate) fn calculate_max_score<AccountId: IdentifierT, P: PerThing>(
 	candidates: &[CandidatePtr<AccountId>],
 	voters: &[Voter<AccountId>],
+	tracing: &mut Vec<PhragmenTrace<AccountId>>,
 ) -> Option<CandidatePtr<AccountId>> {
+	// scores from the previous round, used only for tracing.
+	let previous_scores = candidates.iter().map(|c_ptr| c_ptr.borrow().score).collect::<Vec<_>>();
+
 	for c_ptr in candidates.iter() {
 		let mut candidate = c_ptr.borrow_mut();
 		if !candidate.elected {
@@ -117,7 +161,11 @@ pub(crate) fn calculate_max_score<AccountId: IdentifierT, P: PerThing>(
 			let mut edge_candidate = edge.candidate.borrow_mut();
 			if !edge_candidate.elected {
 				let prev_d = edge_candidate.score.d();
-				edge_candidate.score = Rational128::from(1, denominator_contribution + prev_d);
+				let new_score = Rational128::from(1, denominator_contribution + prev_d);
+				// tracing: CandidateScoreUpdatedByVoter
+				tracing.push(PhragmenTrace::CandidateScoreUpdatedByVoter(CandidateScoreUpdateByVoter { voter: voter.who.clone(), candidate: edge_candidate.who.clone(), score: edge_candidate.score, new_score }));
+
+				edge_candidate.score = new_score;
 			}
 		}
 	}
@@ -126,7 +174,7 @@ pub(crate) fn calculate_max_score<AccountId: IdentifierT, P: PerThing>(
 	let mut best_score = Rational128::zero();
 	let mut best_candidate = None;
 
-	for c_ptr in candidates.iter() {
+	for (c_ptr, previous_score) in candidates.iter().zip(previous_scores.into_iter()) {
 		let mut candidate = c_ptr.borrow_mut();
 		if candidate.approval_stake > 0 {
 			// finalise the score value.
@@ -160,6 +208,11 @@ pub(crate) fn calculate_max_score<AccountId: IdentifierT, P: PerThing>(
 				candidate.approval_stake.checked_mul(one).unwrap_or_else(Bounded::max_value);
 			candidate.score = Rational128::from(score_n, score_d);
 
+			if !candidate.elected {
+				// tracing: CandidateScoreUpdated
+				tracing.push(PhragmenTrace::CandidateScoreUpdated(CandidateScoreUpdate { who: candidate.who.clone(), score: previous_score, new_score: candidate.score }));
+			}
+
 			// check if we have a new winner.
 			if !candidate.elected && candidate.score > best_score {
 				best_score = candidate.score;
@@ -170,6 +223,9 @@ pub(crate) fn calculate_max_score<AccountId: IdentifierT, P: PerThing>(
 		}
 	}
 
+	// tracing: CandidateScoresCalculated
+	tracing.push(PhragmenTrace::CandidateScoresCalculated(candidates.iter().map(|c_ptr| c_ptr.borrow().clone()).collect()));
+
 	best_candidate
 }
 
@@ -182,6 +238,7 @@ pub(crate) fn calculate_max_score<AccountId: IdentifierT, P: PerThing>(
 pub(crate) fn apply_elected<AccountId: IdentifierT>(
 	voters: &mut Vec<Voter<AccountId>>,
 	elected_ptr: CandidatePtr<AccountId>,
+	tracing: &mut Vec<PhragmenTrace<AccountId>>,
 ) {
 	let elected_who = elected_ptr.borrow().who.clone();
 	let cutoff = elected_ptr
@@ -209,6 +266,9 @@ pub(crate) fn apply_elected<AccountId: IdentifierT>(
 					let stake_to_take =
 						edge.weight.saturating_mul(cutoff) / edge_candidate.backed_stake.max(1);
 
+					// tracing: VoterEdgeWeightUpdated
+					tracing.push(PhragmenTrace::VoterEdgeWeightUpdated(EdgeWeightUpdate { voter: voter.who.clone(), candidate: edge.who.clone(), weight: edge.weight, new_weight: edge.weight.saturating_sub(stake_to_take) }));
+
 					// subtract this amount from this edge.
 					edge.weight = edge.weight.saturating_sub(stake_to_take);
 					edge_candidate.backed_stake =
@@ -220,6 +280,9 @@ pub(crate) fn apply_elected<AccountId: IdentifierT>(
 				}
 			}
 
+			// tracing: VoterEdgeWeightUpdated
+			tracing.push(PhragmenTrace::VoterEdgeWeightUpdated(EdgeWeightUpdate { voter: voter.who.clone(), candidate: elected_who.clone(), weight: voter.edges[new_edge_index].weight, new_weight: new_edge_weight }));
+
 			voter.edges[new_edge_index].weight = new_edge_weight;
 		}
 	}
@@ -231,6 +294,7 @@ pub(crate) fn apply_elected<AccountId: IdentifierT>(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::balance;
 	use crate::{Assignment, ElectionResult};
 	use sp_runtime::{Perbill, Percent};
 	use sp_std::rc::Rc;
@@ -247,11 +311,12 @@ mod tests {
 
 		// Round 1
 		let winner =
-			calculate_max_score::<u32, Percent>(candidates.as_ref(), voters.as_ref()).unwrap();
+			calculate_max_score::<u32, Percent>(candidates.as_ref(), voters.as_ref(), &mut vec![])
+				.unwrap();
 		assert_eq!(winner.borrow().who, 3);
 		assert_eq!(winner.borrow().score, 50u32.into());
 
-		apply_elected(&mut voters, Rc::clone(&winner));
+		apply_elected(&mut voters, Rc::clone(&winner), &mut vec![]);
 		assert_eq!(
 			voters
 				.iter()
@@ -280,11 +345,12 @@ mod tests {
 
 		// round 2
 		let winner =
-			calculate_max_score::<u32, Percent>(candidates.as_ref(), voters.as_ref()).unwrap();
+			calculate_max_score::<u32, Percent>(candidates.as_ref(), voters.as_ref(), &mut vec![])
+				.unwrap();
 		assert_eq!(winner.borrow().who, 2);
 		assert_eq!(winner.borrow().score, 25u32.into());
 
-		apply_elected(&mut voters, Rc::clone(&winner));
+		apply_elected(&mut voters, Rc::clone(&winner), &mut vec![]);
 		assert_eq!(
 			voters
 				.iter()
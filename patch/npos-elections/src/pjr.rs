@@ -27,6 +27,7 @@ use crate::{
 	VoteWeight, Voter,
 };
 use sp_arithmetic::{traits::Zero, Perbill};
+use sp_core::RuntimeDebug;
 use sp_std::{collections::btree_map::BTreeMap, rc::Rc, vec::Vec};
 /// The type used as the threshold.
 ///
@@ -138,6 +139,56 @@ pub fn pjr_check_core<AccountId: IdentifierT>(
 	}
 }
 
+/// Evidence that a solution is not t-PJR.
+///
+/// The voters listed here all vote for `candidate` and could together spare `pre_score >= threshold`
+/// of stake for it, without dropping the backing stake of any elected candidate below `threshold`.
+#[derive(Clone, RuntimeDebug)]
+pub struct PjrCounterExample<AccountId> {
+	/// The unelected candidate proving the violation.
+	pub candidate: AccountId,
+	/// Pre-score of the candidate, i.e. the sum of slacks of all voters.
+	pub pre_score: ExtendedBalance,
+	/// The threshold the solution was checked against.
+	pub threshold: Threshold,
+	/// Voters of the candidate, zipped with their non-zero slack.
+	pub voters: Vec<(AccountId, ExtendedBalance)>,
+}
+
+/// Explain a counter_example returned by [`t_pjr_check`].
+///
+/// Returns the group of voters proving that `counter_example` violates t-PJR, or `None` if the
+/// challenge is not valid (see [`validate_t_pjr_challenge`]).
+pub fn t_pjr_counter_example<AccountId: IdentifierT>(
+	counter_example: AccountId,
+	supports: &Supports<AccountId>,
+	all_candidates: Vec<AccountId>,
+	all_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	threshold: Threshold,
+) -> Option<PjrCounterExample<AccountId>> {
+	let (candidates, voters) = prepare_pjr_input(supports, all_candidates, all_voters);
+	let candidate = candidates.iter().find(|candidate| candidate.borrow().who == counter_example)?;
+	if candidate.borrow().elected {
+		return None
+	}
+
+	let group = voters
+		.iter()
+		.filter(|v| v.votes_for(&counter_example))
+		.map(|v| (v.who.clone(), slack(v, threshold)))
+		.filter(|(_, slack)| !slack.is_zero())
+		.collect::<Vec<_>>();
+	let pre_score = group
+		.iter()
+		.fold(Zero::zero(), |acc: ExtendedBalance, (_, slack)| acc.saturating_add(*slack));
+
+	if pre_score >= threshold {
+		Some(PjrCounterExample { candidate: counter_example, pre_score, threshold, voters: group })
+	} else {
+		None
+	}
+}
+
 /// Validate a challenge to an election result.
 ///
 /// A challenge to an election result is valid if there exists some counter_example for which
@@ -903,3 +903,226 @@ mod score {
 		assert!(ElectionScore::from([10, 5, 15]) > ElectionScore::from([10, 5, 25]));
 	}
 }
+
+mod tracing {
+	use super::*;
+	use crate::{
+		balancing::voters_score, phragmms, phragmms_with_tracing, ElectionScore, PhragmenTrace,
+	};
+
+	fn balancing_inputs() -> (Vec<crate::CandidatePtr<AccountId>>, Vec<Voter<AccountId>>) {
+		let candidates = vec![1, 2, 3, 4, 5];
+		let voters = vec![
+			(10, 10, vec![1, 2]),
+			(20, 20, vec![1, 3]),
+			(30, 30, vec![1, 2, 3, 4]),
+			(40, 40, vec![1, 3, 4, 5]),
+			(50, 50, vec![2, 4, 5]),
+		];
+
+		let (candidates, voters) = setup_inputs(candidates, voters);
+		seq_phragmen_core(4, candidates, voters).unwrap()
+	}
+
+	fn edge_weights(
+		voters: &[Voter<AccountId>],
+	) -> Vec<(AccountId, Vec<(AccountId, ExtendedBalance)>)> {
+		voters
+			.iter()
+			.map(|v| (v.who, v.edges.iter().map(|e| (e.who, e.weight)).collect()))
+			.collect()
+	}
+
+	#[test]
+	fn balance_with_tracing_matches_balance() {
+		let config = BalancingConfig { iterations: 4, tolerance: 0 };
+		let (_, mut voters) = balancing_inputs();
+		let iterations = balancing::balance(&mut voters, &config);
+		let (_, mut traced_voters) = balancing_inputs();
+		let initial_score = voters_score(&traced_voters);
+		let (traced_iterations, tracing) =
+			balancing::balance_with_tracing(&mut traced_voters, &config);
+
+		assert_eq!(traced_iterations, iterations);
+		assert_eq!(edge_weights(&traced_voters), edge_weights(&voters));
+
+		assert!(matches!(tracing.first(), Some(PhragmenTrace::BalancingStart)));
+		let traced_scores = tracing
+			.iter()
+			.filter_map(|t| match t {
+				PhragmenTrace::BalancingIteration(update) => Some((update.iteration, update.score)),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(traced_scores.len(), iterations);
+		assert_eq!(traced_scores.last().map(|(iteration, _)| *iteration), Some(iterations));
+		assert_eq!(traced_scores.last().map(|(_, score)| *score), Some(voters_score(&voters)));
+		assert!(matches!(
+			tracing.get(tracing.len() - 2),
+			Some(PhragmenTrace::BalancingFinish(i)) if *i == iterations
+		));
+		match tracing.last() {
+			Some(PhragmenTrace::ElectionScoreUpdated(update)) => {
+				assert_eq!(update.score, initial_score);
+				assert_eq!(update.new_score, voters_score(&voters));
+			},
+			_ => panic!("balancing trace must end with the score update"),
+		}
+	}
+
+	#[test]
+	fn balance_with_tracing_without_iterations() {
+		let config = BalancingConfig { iterations: 0, tolerance: 0 };
+		let (_, mut voters) = balancing_inputs();
+		let before = edge_weights(&voters);
+
+		let (iterations, tracing) = balancing::balance_with_tracing(&mut voters, &config);
+
+		assert_eq!(iterations, 0);
+		assert_eq!(edge_weights(&voters), before);
+		assert_eq!(tracing.len(), 3);
+		assert!(matches!(tracing[1], PhragmenTrace::BalancingFinish(0)));
+		assert!(matches!(
+			&tracing[2],
+			PhragmenTrace::ElectionScoreUpdated(update) if update.score == update.new_score
+		));
+	}
+
+	#[test]
+	fn voters_score_works() {
+		let (_, mut voters) = balancing_inputs();
+		balancing::balance(&mut voters, &BalancingConfig { iterations: 4, tolerance: 0 });
+
+		// see `balancing_core_works`: elected candidates are backed by 37, 38, 37 and 38.
+		assert_eq!(
+			voters_score(&voters),
+			ElectionScore {
+				minimal_stake: 37,
+				sum_stake: 150,
+				sum_stake_squared: 2 * 37 * 37 + 2 * 38 * 38,
+			}
+		);
+	}
+
+	#[test]
+	fn phragmms_with_tracing_matches_phragmms() {
+		let candidates = vec![11, 21, 31, 41, 51, 61, 71];
+		let voters = vec![
+			(2, 2000, vec![11]),
+			(4, 1000, vec![11, 21]),
+			(6, 1000, vec![21, 31]),
+			(8, 1000, vec![31, 41]),
+			(110, 1000, vec![41, 51]),
+			(120, 1000, vec![51, 61]),
+			(130, 1000, vec![61, 71]),
+		];
+		let config = BalancingConfig { iterations: 2, tolerance: 0 };
+
+		let ElectionResult::<_, Perbill> { winners, assignments } =
+			phragmms(4, candidates.clone(), voters.clone(), Some(config)).unwrap();
+		let (traced, all_candidates, tracing) =
+			phragmms_with_tracing::<AccountId, Perbill>(4, candidates, voters, Some(config))
+				.unwrap();
+
+		assert_eq!(traced.winners, winners);
+		assert_eq!(traced.winners, vec![(11, 3000), (31, 2000), (51, 1500), (61, 1500)]);
+		assert_eq!(traced.assignments, assignments);
+
+		// all candidates are returned, winners being marked as elected.
+		assert_eq!(
+			all_candidates
+				.iter()
+				.filter(|c_ptr| c_ptr.borrow().elected)
+				.map(|c_ptr| c_ptr.borrow().who)
+				.collect::<Vec<_>>(),
+			vec![11, 31, 51, 61],
+		);
+
+		// one round, one elected candidate and one balancing per winner.
+		assert!(matches!(tracing.first(), Some(PhragmenTrace::Start)));
+		assert!(matches!(tracing.get(1), Some(PhragmenTrace::ToElect(4))));
+		assert!(matches!(tracing.last(), Some(PhragmenTrace::Finish)));
+		let count =
+			|f: fn(&PhragmenTrace<AccountId>) -> bool| tracing.iter().filter(|t| f(t)).count();
+		assert_eq!(count(|t| matches!(t, PhragmenTrace::RoundStart(..))), 4);
+		assert_eq!(count(|t| matches!(t, PhragmenTrace::RoundEnd(..))), 4);
+		assert_eq!(count(|t| matches!(t, PhragmenTrace::BalancingStart)), 4);
+		assert_eq!(
+			tracing
+				.iter()
+				.filter_map(|t| match t {
+					PhragmenTrace::CandidateElected(candidate) => Some(candidate.who),
+					_ => None,
+				})
+				.collect::<Vec<_>>(),
+			vec![11, 31, 51, 61],
+		);
+	}
+}
+
+mod pjr {
+	use super::*;
+	use crate::{t_pjr_check, t_pjr_counter_example, Supports, VoteWeight};
+
+	fn inputs() -> (Supports<AccountId>, Vec<AccountId>, Vec<(AccountId, VoteWeight, Vec<AccountId>)>)
+	{
+		// 3 votes for 10 and 30, but its stake is ignored by the solution.
+		let supports = vec![
+			(20, Support { total: 15, voters: vec![(1, 5), (2, 10)] }),
+			(40, Support { total: 15, voters: vec![(1, 5), (2, 10)] }),
+		];
+		let all_candidates = vec![10, 20, 30, 40];
+		let all_voters = vec![
+			(1, 10, vec![10, 20, 30, 40]),
+			(2, 20, vec![10, 20, 30, 40]),
+			(3, 30, vec![10, 30]),
+		];
+
+		(supports, all_candidates, all_voters)
+	}
+
+	#[test]
+	fn t_pjr_counter_example_works() {
+		let (supports, all_candidates, all_voters) = inputs();
+		let threshold = 20;
+
+		// winners are backed by less than the threshold, so 1 and 2 have no slack left, while 3
+		// can spare all of its stake.
+		let counter_example =
+			t_pjr_check(&supports, all_candidates.clone(), all_voters.clone(), threshold)
+				.unwrap_err();
+		assert_eq!(counter_example, 30);
+
+		let explained = t_pjr_counter_example(
+			counter_example,
+			&supports,
+			all_candidates,
+			all_voters,
+			threshold,
+		)
+		.unwrap();
+		assert_eq!(explained.candidate, 30);
+		assert_eq!(explained.pre_score, 30);
+		assert_eq!(explained.threshold, threshold);
+		assert_eq!(explained.voters, vec![(3, 30)]);
+	}
+
+	#[test]
+	fn t_pjr_counter_example_rejects_invalid_challenges() {
+		let (supports, all_candidates, all_voters) = inputs();
+
+		// elected candidates can't be counter examples.
+		assert!(t_pjr_counter_example(
+			20,
+			&supports,
+			all_candidates.clone(),
+			all_voters.clone(),
+			20
+		)
+		.is_none());
+
+		// nor can candidates whose voters can't spare enough stake.
+		assert!(t_pjr_check(&supports, all_candidates.clone(), all_voters.clone(), 31).is_ok());
+		assert!(t_pjr_counter_example(30, &supports, all_candidates, all_voters, 31).is_none());
+	}
+}
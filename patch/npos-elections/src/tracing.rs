@@ -1,4 +1,4 @@
-use crate::{Candidate, Rational128, Voter};
+use crate::{Candidate, ElectionScore, ExtendedBalance, Rational128, Voter};
 
 pub type Candidates<AccountId> = Vec<Candidate<AccountId>>;
 pub type Voters<AccountId> = Vec<Voter<AccountId>>;
@@ -18,6 +18,14 @@ pub struct EdgeLoadUpdate<AccountId> {
     pub new_load: Rational128,
 }
 
+#[derive(Clone)]
+pub struct EdgeWeightUpdate<AccountId> {
+    pub voter: AccountId,
+    pub candidate: AccountId,
+    pub weight: ExtendedBalance,
+    pub new_weight: ExtendedBalance,
+}
+
 #[derive(Clone)]
 pub struct CandidateScoreUpdate<AccountId> {
     pub who: AccountId,
@@ -33,6 +41,20 @@ pub struct CandidateScoreUpdateByVoter<AccountId> {
     pub new_score: Rational128,
 }
 
+#[derive(Clone)]
+pub struct BalancingIterationUpdate<AccountId> {
+    pub iteration: usize,
+    pub max_diff: ExtendedBalance,
+    pub edges: Vec<EdgeWeightUpdate<AccountId>>,
+    pub score: ElectionScore,
+}
+
+#[derive(Clone)]
+pub struct ElectionScoreUpdate {
+    pub score: ElectionScore,
+    pub new_score: ElectionScore,
+}
+
 /// Trace of internal step inside Phragmen.
 #[derive(Clone)]
 pub enum PhragmenTrace<AccountId> {
@@ -50,4 +72,11 @@ pub enum PhragmenTrace<AccountId> {
     VoterLoadUpdated(LoadUpdate<AccountId>),
     CandidateScoreUpdated(CandidateScoreUpdate<AccountId>),
     CandidateScoreUpdatedByVoter(CandidateScoreUpdateByVoter<AccountId>),
+    ComputeMaxScores,
+    ApplyElected,
+    VoterEdgeWeightUpdated(EdgeWeightUpdate<AccountId>),
+    BalancingStart,
+    BalancingIteration(BalancingIterationUpdate<AccountId>),
+    BalancingFinish(usize),
+    ElectionScoreUpdated(ElectionScoreUpdate),
 }
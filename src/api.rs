@@ -1,44 +1,277 @@
 use super::*;
-use actix_web::Result;
+use actix_web::http::header::{
+    self, CacheControl, CacheDirective, ContentType, ETag, EntityTag, Header, IfNoneMatch,
+};
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse};
+
+/// Register all API routes
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    // Malformed paths and queries are reported as problem details, like other errors
+    cfg.app_data(
+        web::PathConfig::default()
+            .error_handler(|e, _| Error::BadRequest(format!("Invalid path: {}", e)).into()),
+    )
+    .app_data(
+        web::QueryConfig::default()
+            .error_handler(|e, _| Error::BadRequest(format!("Invalid query: {}", e)).into()),
+    )
+    .service(network)
+    .service(council_elections_latest)
+    .service(council_elections_history)
+    .service(council_elections_at_blocknumber)
+    .service(council_elections_at_blockhash)
+    .service(council_elections_pjr_at_blockhash)
+    .service(council_elections_reconcile_at_blockhash);
+}
+
+#[get("/network")]
+async fn network(network: web::Data<Network>) -> impl Responder {
+    web::Json(ApiNetwork::from(network.profile()))
+}
 
 #[get("/council/elections/latest")]
 async fn council_elections_latest(
-    onchain: web::Data<OnchainDataProvider<SubstrateConfig>>,
-) -> Result<impl Responder> {
-    let onchain_data = onchain
-        .elections_at_blockhash(None)
-        .await
-        .map_err(|_| error::ErrorBadRequest("Error downloading on-chain elections data"))?;
-    let phragmen = simulate_weighted_phragmen_elections(&onchain_data)?;
-    let mut result = ApiElectionData::build_from(&onchain_data, &phragmen);
+    query: web::Query<ApiElectionsQuery>,
+    onchain: web::Data<AppDataProvider>,
+    in_flight: web::Data<ElectionsInFlight>,
+    latest: web::Data<LatestElections>,
+) -> Result<HttpResponse, Error> {
+    // Answer default queries from memory, along with age (in seconds) of the data
+    if let Some(precomputed) = latest.get().filter(|_| query.is_default()) {
+        return Ok(HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .insert_header((
+                header::AGE,
+                precomputed.computed_at.elapsed().as_secs().to_string(),
+            ))
+            .body(precomputed.body));
+    }
 
-    // Map addresses to identities
-    onchain
-        .map_elections_identities(&mut result)
-        .await
-        .map_err(|_| error::ErrorBadRequest("Error mapping addresses to identites"))?;
+    let body = elections_json(&onchain, &in_flight, None, &query).await?;
+
+    Ok(json_response(body))
+}
 
-    Ok(web::Json(result))
+#[get("/council/elections/block/{block_number}")]
+async fn council_elections_at_blocknumber(
+    path: web::Path<u32>,
+    query: web::Query<ApiElectionsQuery>,
+    onchain: web::Data<AppDataProvider>,
+    in_flight: web::Data<ElectionsInFlight>,
+) -> Result<HttpResponse, Error> {
+    let block_number = path.into_inner();
+    let block_hash = onchain.blockhash_at(block_number).await?;
+    let body = elections_json(&onchain, &in_flight, Some(block_hash), &query).await?;
+
+    Ok(json_response(body))
+}
+
+#[get("/council/elections/history")]
+async fn council_elections_history(
+    history: web::Data<ElectionsHistory>,
+) -> Result<impl Responder, Error> {
+    let terms: Vec<ApiElectionTerm> = history.terms().iter().map(ApiElectionTerm::from).collect();
+
+    Ok(web::Json(terms))
 }
 
 #[get("/council/elections/{block_hash}")]
 async fn council_elections_at_blockhash(
+    req: HttpRequest,
+    path: web::Path<Hash>,
+    query: web::Query<ApiElectionsQuery>,
+    onchain: web::Data<AppDataProvider>,
+    in_flight: web::Data<ElectionsInFlight>,
+) -> Result<HttpResponse, Error> {
+    let block_hash = path.into_inner();
+
+    // Current identities change over time, so such responses are neither cached nor immutable
+    if query.current_identities {
+        let body = elections_json(&onchain, &in_flight, Some(block_hash), &query).await?;
+        return Ok(json_response(body));
+    }
+
+    let key = format!("{:?}-{}", block_hash, query.cache_key());
+    let body = match onchain.cache().election_result(&key) {
+        Some(body) => body,
+        None => {
+            let body = elections_json(&onchain, &in_flight, Some(block_hash), &query).await?;
+            onchain.cache().insert_election_result(&key, body.clone());
+            body
+        }
+    };
+
+    Ok(immutable_json(&req, &key, body))
+}
+
+#[get("/council/elections/{block_hash}/pjr")]
+async fn council_elections_pjr_at_blockhash(
+    req: HttpRequest,
     path: web::Path<Hash>,
-    onchain: web::Data<OnchainDataProvider<SubstrateConfig>>,
-) -> Result<impl Responder> {
+    query: web::Query<ApiElectionsQuery>,
+    onchain: web::Data<AppDataProvider>,
+) -> Result<HttpResponse, Error> {
     let block_hash = path.into_inner();
-    let onchain_data = onchain
-        .elections_at_blockhash(Some(block_hash))
+    let onchain_data = onchain.elections_at_blockhash(Some(block_hash)).await?;
+    let pjr = check_pjr(&onchain_data, &query.simulation_params())?;
+    let body = to_json(&ApiPjrCheck::build_from(&onchain_data, &pjr))?;
+
+    let key = format!("{:?}-pjr-{}", block_hash, query.cache_key());
+    Ok(immutable_json(&req, &key, body))
+}
+
+#[get("/council/elections/{block_hash}/reconcile")]
+async fn council_elections_reconcile_at_blockhash(
+    req: HttpRequest,
+    path: web::Path<Hash>,
+    query: web::Query<ApiElectionsQuery>,
+    onchain: web::Data<AppDataProvider>,
+) -> Result<HttpResponse, Error> {
+    let block_hash = path.into_inner();
+    let reconciliation = reconcile_election_at(onchain.get_ref(), block_hash, &query).await?;
+    let body = to_json(&ApiReconciliation::build_from(&reconciliation))?;
+
+    let key = format!("{:?}-reconcile-{}", block_hash, query.cache_key());
+    Ok(immutable_json(&req, &key, body))
+}
+
+/// Serialize API response
+fn to_json(value: &impl Serialize) -> Result<Bytes, Error> {
+    serde_json::to_vec(value)
+        .map(Bytes::from)
+        .map_err(|e| Error::Internal(e.to_string()))
+}
+
+/// Respond with serialized JSON
+fn json_response(body: Bytes) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(body)
+}
+
+/// Respond with JSON that never changes for given key (data at block hash is immutable)
+fn immutable_json(req: &HttpRequest, key: &str, body: Bytes) -> HttpResponse {
+    let etag = EntityTag::new_strong(format!("v{}-{}", env!("CARGO_PKG_VERSION"), key));
+    let not_modified = match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+        Err(_) => false,
+    };
+
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response
+        .insert_header(ETag(etag))
+        .insert_header(CacheControl(vec![
+            CacheDirective::Public,
+            CacheDirective::MaxAge(31_536_000),
+            CacheDirective::Extension("immutable".to_string(), None),
+        ]));
+
+    if not_modified {
+        response.finish()
+    } else {
+        response.content_type(ContentType::json()).body(body)
+    }
+}
+
+/// Simulate election held at given block on data from its parent block and compare results
+pub async fn reconcile_election_at(
+    onchain: &impl OnchainElectionsDataProvider,
+    block_hash: Hash,
+    query: &ApiElectionsQuery,
+) -> Result<ReconciliationOutputs, Error> {
+    let parent_hash = onchain.parent_blockhash(block_hash).await?;
+    let parent_data = onchain.elections_at_blockhash(Some(parent_hash)).await?;
+    let onchain_data = onchain.elections_at_blockhash(Some(block_hash)).await?;
+
+    reconcile_election(&parent_data, &onchain_data, &query.simulation_params())
+}
+
+/// Compute latest elections data (default query) at given block ahead of requests
+pub async fn precompute_latest_elections(
+    onchain: AppDataProvider,
+    latest: LatestElections,
+    block_hash: Hash,
+) {
+    let query = ApiElectionsQuery::default();
+    let result = elections_data(&onchain, block_hash, &query)
+        .await
+        .and_then(|result| to_json(&result));
+    match result {
+        Ok(body) => {
+            event!(
+                Level::INFO,
+                "Precomputed latest elections data at block hash {:?}",
+                block_hash
+            );
+            latest.set(block_hash, body);
+        }
+        Err(e) => event!(
+            Level::WARN,
+            "Error precomputing latest elections data at block hash {:?}: {}",
+            block_hash,
+            e
+        ),
+    }
+}
+
+/// Elections data requests in progress, by block hash and query
+pub type ElectionsInFlight = SingleFlight<String, Bytes>;
+
+/// Elections data serialized to JSON (latest block if no block hash is given).
+/// Concurrent identical requests share a single download and simulation.
+pub async fn elections_json(
+    onchain: &AppDataProvider,
+    in_flight: &ElectionsInFlight,
+    block_hash: Option<Hash>,
+    query: &ApiElectionsQuery,
+) -> Result<Bytes, Error> {
+    let data_hash = match block_hash {
+        Some(block_hash) => block_hash,
+        None => onchain.latest_blockhash(query.at).await?,
+    };
+
+    let key = format!(
+        "{:?}-{}-{}",
+        data_hash,
+        query.cache_key(),
+        query.current_identities
+    );
+    in_flight
+        .run(key, || async {
+            let result = elections_data(onchain, data_hash, query).await?;
+            to_json(&result)
+        })
         .await
-        .map_err(|_| error::ErrorBadRequest("Error downloading on-chain elections data"))?;
-    let phragmen = simulate_weighted_phragmen_elections(&onchain_data)?;
+}
+
+/// Download on-chain data, simulate elections and map identities
+async fn elections_data(
+    onchain: &AppDataProvider,
+    data_hash: Hash,
+    query: &ApiElectionsQuery,
+) -> Result<ApiElectionData, Error> {
+    let onchain_data = onchain.elections_at_blockhash(Some(data_hash)).await?;
+    let phragmen = simulate_weighted_phragmen_elections(&onchain_data, &query.simulation_params())?;
     let mut result = ApiElectionData::build_from(&onchain_data, &phragmen);
 
-    // Map addresses to identities
+    // Map addresses to identities at the block elections data was taken from
     onchain
-        .map_elections_identities(&mut result)
-        .await
-        .map_err(|_| error::ErrorBadRequest("Error mapping addresses to identites"))?;
+        .map_elections_identities(
+            &mut result,
+            onchain_data.block_hash,
+            query.current_identities,
+        )
+        .await?;
+
+    if query.merge_by_parent {
+        result.entities = Some(ApiEntity::group_voters(&result.voters));
+    }
 
-    Ok(web::Json(result))
+    Ok(result)
 }
@@ -0,0 +1,86 @@
+use super::*;
+use std::sync::Arc;
+use subxt::blocks::Extrinsics;
+use subxt::events::Events;
+use tokio::sync::broadcast;
+
+/// Number of finalized blocks buffered for followers which are behind
+const FINALIZED_BLOCKS_CAPACITY: usize = 64;
+
+/// Finalized block, with its extrinsics and events
+pub struct FinalizedBlock {
+    pub number: u32,
+    pub hash: Hash,
+    pub extrinsics: Extrinsics<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    pub events: Events<SubstrateConfig>,
+}
+
+/// Finalized blocks followed with a single subscription, whose bodies and events are
+/// fetched once and shared by all followers
+#[derive(Clone)]
+pub struct FinalizedBlocks {
+    sender: broadcast::Sender<Arc<FinalizedBlock>>,
+}
+
+impl Default for FinalizedBlocks {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(FINALIZED_BLOCKS_CAPACITY).0,
+        }
+    }
+}
+
+impl FinalizedBlocks {
+    /// Receive blocks finalized from now on
+    pub fn subscribe(&self) -> FinalizedBlocksReceiver {
+        FinalizedBlocksReceiver(self.sender.subscribe())
+    }
+
+    /// Keep following finalized blocks as long as the node is reachable
+    pub async fn follow(self, api: OnlineClient<SubstrateConfig>) {
+        loop {
+            if let Err(e) = self.publish(&api).await {
+                event!(Level::WARN, "Error following finalized blocks: {}", e);
+            }
+            actix_web::rt::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    async fn publish(&self, api: &OnlineClient<SubstrateConfig>) -> Result<()> {
+        let mut blocks = api.blocks().subscribe_finalized().await?;
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let block = FinalizedBlock {
+                number: block.number(),
+                hash: block.hash(),
+                extrinsics: block.extrinsics().await?,
+                events: block.events().await?,
+            };
+            // Sending only fails if nobody follows blocks
+            let _ = self.sender.send(Arc::new(block));
+        }
+
+        Ok(())
+    }
+}
+
+/// Receiver of finalized blocks.
+///
+/// Blocks are skipped if the receiver falls behind (or the subscription is renewed),
+/// so followers must handle gaps in block numbers.
+pub struct FinalizedBlocksReceiver(broadcast::Receiver<Arc<FinalizedBlock>>);
+
+impl FinalizedBlocksReceiver {
+    /// Next finalized block (None once blocks are no longer followed)
+    pub async fn next(&mut self) -> Option<Arc<FinalizedBlock>> {
+        loop {
+            match self.0.recv().await {
+                Ok(block) => return Some(block),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    event!(Level::WARN, "Skipped {} finalized blocks", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use super::*;
+use actix_web::web::Bytes;
+use lru::LruCache;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash as StdHash;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+/// Number of insertions into a `TtlCache` between sweeps of expired entries
+const TTL_CACHE_SWEEP_INTERVAL: usize = 1024;
+
+/// Key-value cache with entries expiring after a fixed time, shared among all clones
+#[derive(Clone)]
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    entries: Arc<RwLock<HashMap<K, (Instant, V)>>>,
+    /// Insertions since the cache was created, used to schedule sweeps
+    inserts: Arc<AtomicUsize>,
+}
+
+impl<K: Eq + StdHash, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Default::default(),
+            inserts: Default::default(),
+        }
+    }
+
+    /// Get value if present and not expired
+    pub fn get(&self, key: &K) -> Option<V> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(key)
+            .filter(|(inserted, _)| inserted.elapsed() < self.ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Insert value, replacing previous one
+    pub fn insert(&self, key: K, value: V) {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        // Expired entries are dropped every few insertions, so that the cache doesn't grow
+        // indefinitely (expired entries left in between are never returned)
+        if self.inserts.fetch_add(1, Ordering::Relaxed) % TTL_CACHE_SWEEP_INTERVAL == 0 {
+            entries.retain(|_, (inserted, _)| inserted.elapsed() < self.ttl);
+        }
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    /// Remove entries matching predicate
+    pub fn invalidate(&self, predicate: impl Fn(&K, &V) -> bool) {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|key, (_, value)| !predicate(key, value));
+    }
+}
+
+/// Prefix of keys and tree names of all state persisted by this version
+const STORE_VERSION_PREFIX: &str = concat!("v", env!("CARGO_PKG_VERSION"), "/");
+
+/// Two-level cache of immutable data addressed by block hash: in-memory LRU in front of
+/// optional on-disk LRU. Entries are stored per crate version, so that upgrades don't serve
+/// stale formats.
+#[derive(Clone)]
+pub struct ElectionsCache {
+    memory: Arc<Mutex<LruCache<Vec<u8>, Bytes>>>,
+    disk: Option<DiskCache>,
+}
+
+impl ElectionsCache {
+    pub fn new(capacity: usize, dir: Option<&Path>, disk_capacity: usize) -> Result<Self> {
+        let capacity = NonZeroUsize::new(capacity)
+            .ok_or_else(|| anyhow::anyhow!("Cache capacity must be positive"))?;
+        let disk = match dir {
+            Some(dir) => Some(DiskCache::open(sled::open(dir)?, disk_capacity)?),
+            None => None,
+        };
+
+        Ok(Self {
+            memory: Arc::new(Mutex::new(LruCache::new(capacity))),
+            disk,
+        })
+    }
+
+    /// On-disk store, if any (shared with other persisted state)
+    pub fn store(&self) -> Option<&sled::Db> {
+        self.disk.as_ref().map(|disk| &disk.db)
+    }
+
+    /// On-chain elections data at given block hash
+    pub fn elections_data(&self, block_hash: &Hash) -> Option<OnchainElectionsData> {
+        let bytes = self.get(&Self::data_key(block_hash))?;
+        OnchainElectionsData::decode_versioned(&bytes)
+            .inspect_err(|e| event!(Level::WARN, "Cached elections data not decodable: {}", e))
+            .ok()
+    }
+
+    pub fn insert_elections_data(&self, data: &OnchainElectionsData) {
+        self.insert(
+            Self::data_key(&data.block_hash),
+            Bytes::from(data.encode_versioned()),
+        );
+    }
+
+    /// Serialized election result identified by `key`
+    pub fn election_result(&self, key: &str) -> Option<Bytes> {
+        self.get(&Self::result_key(key))
+    }
+
+    pub fn insert_election_result(&self, key: &str, json: Bytes) {
+        self.insert(Self::result_key(key), json);
+    }
+
+    fn data_key(block_hash: &Hash) -> Vec<u8> {
+        format!("data/{:?}", block_hash).into_bytes()
+    }
+
+    fn result_key(key: &str) -> Vec<u8> {
+        format!("result/{}", key).into_bytes()
+    }
+
+    /// Get value from memory, falling back to disk
+    fn get(&self, key: &[u8]) -> Option<Bytes> {
+        let mut memory = self.memory.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(value) = memory.get(key) {
+            return Some(value.clone());
+        }
+
+        let value = self
+            .disk
+            .as_ref()?
+            .get(key)
+            .inspect_err(|e| event!(Level::WARN, "Error reading disk cache: {}", e))
+            .ok()??;
+        memory.put(key.to_vec(), value.clone());
+
+        Some(value)
+    }
+
+    /// Insert value to memory and disk (disk errors are only logged)
+    fn insert(&self, key: Vec<u8>, value: Bytes) {
+        if let Some(Err(e)) = self.disk.as_ref().map(|disk| disk.insert(&key, &value[..])) {
+            event!(Level::WARN, "Error writing disk cache: {}", e);
+        }
+        let mut memory = self.memory.lock().unwrap_or_else(|e| e.into_inner());
+        memory.put(key, value);
+    }
+}
+
+/// On-disk cache entries, bounded by entry count with least recently used ones evicted.
+///
+/// Entries of the current version are kept in their own tree, along with trees recording
+/// their order of use. State of other versions is purged when the store is opened.
+#[derive(Clone)]
+struct DiskCache {
+    db: sled::Db,
+    /// Entry key -> value
+    entries: sled::Tree,
+    /// Entry key -> sequence number of last use
+    uses: sled::Tree,
+    /// Sequence number of last use -> entry key, from least to most recently used
+    order: sled::Tree,
+    capacity: usize,
+    /// Number of entries, locked while entries and their order of use are updated
+    len: Arc<Mutex<usize>>,
+}
+
+impl DiskCache {
+    fn open(db: sled::Db, capacity: usize) -> Result<Self> {
+        if capacity == 0 {
+            anyhow::bail!("Disk cache capacity must be positive");
+        }
+        Self::purge_other_versions(&db)?;
+
+        let tree = |name: &str| db.open_tree(format!("{}cache/{}", STORE_VERSION_PREFIX, name));
+        let cache = Self {
+            entries: tree("entries")?,
+            uses: tree("uses")?,
+            order: tree("order")?,
+            db,
+            capacity,
+            len: Default::default(),
+        };
+
+        let mut len = cache.len.lock().unwrap_or_else(|e| e.into_inner());
+        *len = cache.uses.len();
+        // Entries without recorded use (e.g. after a crash) become most recently used
+        for key in cache.entries.iter().keys() {
+            let key = key?;
+            if !cache.uses.contains_key(&key)? {
+                cache.touch(&key, &mut len)?;
+            }
+        }
+        cache.evict(&mut len)?;
+        drop(len);
+
+        Ok(cache)
+    }
+
+    /// Remove keys and trees of other versions
+    fn purge_other_versions(db: &sled::Db) -> Result<()> {
+        let mut purged = 0;
+        for key in db.iter().keys() {
+            let key = key?;
+            if !key.starts_with(STORE_VERSION_PREFIX.as_bytes()) {
+                db.remove(key)?;
+                purged += 1;
+            }
+        }
+        for name in db.tree_names() {
+            if name != db.name() && !name.starts_with(STORE_VERSION_PREFIX.as_bytes()) {
+                db.drop_tree(name)?;
+                purged += 1;
+            }
+        }
+        if purged > 0 {
+            event!(
+                Level::INFO,
+                "Purged {} keys and trees of other versions from disk cache",
+                purged
+            );
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, key: &[u8]) -> sled::Result<Option<Bytes>> {
+        let mut len = self.len.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(value) = self.entries.get(key)? else {
+            return Ok(None);
+        };
+        self.touch(key, &mut len)?;
+
+        Ok(Some(Bytes::copy_from_slice(&value)))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> sled::Result<()> {
+        let mut len = self.len.lock().unwrap_or_else(|e| e.into_inner());
+        self.entries.insert(key, value)?;
+        self.touch(key, &mut len)?;
+        self.evict(&mut len)
+    }
+
+    /// Record use of entry (counted in `len` if new)
+    fn touch(&self, key: &[u8], len: &mut usize) -> sled::Result<()> {
+        let sequence = self.db.generate_id()?.to_be_bytes();
+        match self.uses.insert(key, &sequence[..])? {
+            Some(previous) => {
+                self.order.remove(previous)?;
+            }
+            None => *len += 1,
+        }
+        self.order.insert(sequence, key)?;
+
+        Ok(())
+    }
+
+    /// Remove least recently used entries above capacity
+    fn evict(&self, len: &mut usize) -> sled::Result<()> {
+        while *len > self.capacity {
+            let Some((_, key)) = self.order.pop_min()? else {
+                break;
+            };
+            self.uses.remove(&key)?;
+            self.entries.remove(&key)?;
+            *len -= 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// Coalesces concurrent computations of the same key, so that only one of them runs
+/// and the others wait for its result (failed computations are not shared)
+#[derive(Clone)]
+pub struct SingleFlight<K, V> {
+    in_flight: Arc<Mutex<HashMap<K, Arc<tokio::sync::OnceCell<V>>>>>,
+}
+
+impl<K, V> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self {
+            in_flight: Default::default(),
+        }
+    }
+}
+
+impl<K: Eq + StdHash + Clone, V: Clone> SingleFlight<K, V> {
+    pub async fn run<E, F, Fut>(&self, key: K, compute: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        let cell = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            in_flight.entry(key.clone()).or_default().clone()
+        };
+
+        let result = cell.get_or_try_init(compute).await.cloned();
+
+        // Finished computations are forgotten, so that later requests get fresh values
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(&key);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ttl_cache_expires_entries() {
+        let cache = TtlCache::new(Duration::from_millis(50));
+        cache.insert(1, "one");
+        assert_eq!(cache.get(&1), Some("one"));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&1), None);
+
+        // Expired entries are swept once per interval
+        for key in 2..TTL_CACHE_SWEEP_INTERVAL + 2 {
+            cache.insert(key, "other");
+        }
+        let entries = cache.entries.read().unwrap();
+        assert!(!entries.contains_key(&1));
+        assert_eq!(entries.len(), TTL_CACHE_SWEEP_INTERVAL);
+    }
+
+    #[test]
+    fn disk_cache_evicts_least_recently_used_entries() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        db.insert("v0.0.0/history", "old").unwrap();
+        db.open_tree("v0.0.0/cache/entries").unwrap();
+
+        let cache = DiskCache::open(db.clone(), 2).unwrap();
+        assert!(db.get("v0.0.0/history").unwrap().is_none());
+        assert!(
+            !db.tree_names()
+                .contains(&sled::IVec::from("v0.0.0/cache/entries"))
+        );
+
+        cache.insert(b"1", b"one").unwrap();
+        cache.insert(b"2", b"two").unwrap();
+        assert!(cache.get(b"1").unwrap().is_some());
+        cache.insert(b"3", b"three").unwrap();
+
+        assert!(cache.get(b"2").unwrap().is_none());
+        assert_eq!(cache.get(b"1").unwrap(), Some(Bytes::from_static(b"one")));
+        assert_eq!(cache.get(b"3").unwrap(), Some(Bytes::from_static(b"three")));
+
+        // Entries and their order of use persist
+        drop(cache);
+        let cache = DiskCache::open(db, 2).unwrap();
+        cache.insert(b"4", b"four").unwrap();
+        assert!(cache.get(b"1").unwrap().is_none());
+        assert!(cache.get(b"3").unwrap().is_some());
+    }
+
+    #[actix_web::test]
+    async fn single_flight_coalesces_concurrent_callers() {
+        let single_flight = SingleFlight::<u32, u32>::default();
+        let computations = AtomicUsize::new(0);
+        let compute = || async {
+            computations.fetch_add(1, Ordering::SeqCst);
+            actix_web::rt::time::sleep(Duration::from_millis(50)).await;
+            Ok::<_, ()>(42)
+        };
+
+        let results = tokio::join!(
+            single_flight.run(1, compute),
+            single_flight.run(1, compute),
+            single_flight.run(1, compute),
+        );
+
+        assert_eq!(results, (Ok(42), Ok(42), Ok(42)));
+        assert_eq!(computations.load(Ordering::SeqCst), 1);
+
+        // Finished computations are not reused
+        assert_eq!(single_flight.run(1, compute).await, Ok(42));
+        assert_eq!(computations.load(Ordering::SeqCst), 2);
+    }
+}
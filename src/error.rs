@@ -0,0 +1,149 @@
+use super::*;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use subxt::error::RpcError;
+
+/// Errors returned by API endpoints
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Node can't be reached or returned an RPC error
+    #[error("Node unreachable: {0}")]
+    NodeUnreachable(String),
+    /// Block (or snapshot of it) doesn't exist
+    #[error("Block not found: {0}")]
+    BlockNotFound(String),
+    /// State of the block was pruned by the node
+    #[error("Block state discarded: {0}")]
+    StateDiscarded(String),
+    /// Storage item not found at given block (e.g. state pruned or pallet not present)
+    #[error("Storage item not found: {0}")]
+    StorageMissing(String),
+    /// On-chain or snapshot data couldn't be decoded
+    #[error("Decoding error: {0}")]
+    Decode(String),
+    /// Data downloaded from the chain is incomplete
+    #[error("Incomplete elections data: {0}")]
+    IncompleteData(String),
+    /// Phragmen or PhragMMS failed
+    #[error("Phragmen arithmetic error: {0}")]
+    Phragmen(String),
+    /// Request can't be served with given parameters
+    #[error("{0}")]
+    BadRequest(String),
+    /// Any other error
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl Error {
+    /// Short, human-readable summary of the problem type
+    fn title(&self) -> &'static str {
+        match self {
+            Error::NodeUnreachable(_) => "Node unreachable",
+            Error::BlockNotFound(_) => "Block not found",
+            Error::StateDiscarded(_) => "Block state discarded",
+            Error::StorageMissing(_) => "Storage item not found",
+            Error::Decode(_) => "Decoding error",
+            Error::IncompleteData(_) => "Incomplete elections data",
+            Error::Phragmen(_) => "Phragmen arithmetic error",
+            Error::BadRequest(_) => "Bad request",
+            Error::Internal(_) => "Internal error",
+        }
+    }
+}
+
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Error::NodeUnreachable(_) => StatusCode::BAD_GATEWAY,
+            Error::BlockNotFound(_) | Error::StorageMissing(_) => StatusCode::NOT_FOUND,
+            Error::StateDiscarded(_) => StatusCode::GONE,
+            Error::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Error::Decode(_)
+            | Error::IncompleteData(_)
+            | Error::Phragmen(_)
+            | Error::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            event!(Level::ERROR, "{}", self);
+        }
+
+        // RFC 9457 problem details
+        let problem = ApiProblem {
+            problem_type: "about:blank".to_string(),
+            title: self.title().to_string(),
+            status: status.as_u16(),
+            detail: self.to_string(),
+        };
+        HttpResponse::build(status)
+            .content_type("application/problem+json")
+            .body(serde_json::to_string(&problem).unwrap_or_default())
+    }
+}
+
+/// Classify errors coming from data providers
+impl From<anyhow::Error> for Error {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<Error>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<subxt::Error>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<RpcError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+
+        if e.is::<subxt::ext::codec::Error>() || e.is::<serde_json::Error>() {
+            Error::Decode(e.to_string())
+        } else {
+            Error::Internal(format!("{:#}", e))
+        }
+    }
+}
+
+impl From<subxt::Error> for Error {
+    fn from(e: subxt::Error) -> Self {
+        match e {
+            subxt::Error::Rpc(e) => e.into(),
+            subxt::Error::Io(_) => Error::NodeUnreachable(e.to_string()),
+            subxt::Error::Block(_) => Error::BlockNotFound(e.to_string()),
+            subxt::Error::Codec(_) | subxt::Error::Decode(_) => Error::Decode(e.to_string()),
+            _ => Error::Internal(e.to_string()),
+        }
+    }
+}
+
+/// Errors of connections are reported as unreachable node, errors returned by the node
+/// are classified by message (nodes report them as plain strings)
+impl From<RpcError> for Error {
+    fn from(e: RpcError) -> Self {
+        match &e {
+            RpcError::Client(_) | RpcError::DisconnectedWillReconnect(_) => {
+                Error::NodeUnreachable(e.to_string())
+            }
+            RpcError::User(user_error) => {
+                let message = user_error.message.to_lowercase();
+                if message.contains("state already discarded") {
+                    Error::StateDiscarded(user_error.message.clone())
+                } else if message.contains("unknown block")
+                    || message.contains("unknownblock")
+                    || message.contains("header was not found")
+                {
+                    Error::BlockNotFound(user_error.message.clone())
+                } else {
+                    Error::Internal(e.to_string())
+                }
+            }
+            RpcError::Deserialization(_) => Error::Decode(e.to_string()),
+            _ => Error::Internal(e.to_string()),
+        }
+    }
+}
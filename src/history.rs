@@ -0,0 +1,105 @@
+use super::*;
+use std::sync::{Arc, RwLock};
+
+/// Key of the persisted history (includes crate version, like cache trees, so that
+/// history of other versions is purged along with their cache)
+const HISTORY_KEY: &str = concat!("v", env!("CARGO_PKG_VERSION"), "/history");
+
+/// Elections held so far, shared among all workers and filled by the indexer.
+/// With a store, the index is persisted, so that indexing resumes where it stopped.
+#[derive(Clone, Default)]
+pub struct ElectionsHistory {
+    state: Arc<RwLock<HistoryState>>,
+    store: Option<sled::Db>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct HistoryState {
+    /// Terms ordered by block number
+    terms: Vec<ElectionTerm>,
+    /// Last block processed by the indexer
+    indexed_block: Option<u32>,
+}
+
+impl ElectionsHistory {
+    /// History persisted in given store (loading what was indexed so far)
+    pub fn open(store: Option<sled::Db>) -> Result<Self> {
+        let state = match store
+            .as_ref()
+            .map(|store| store.get(HISTORY_KEY))
+            .transpose()?
+        {
+            Some(Some(bytes)) => serde_json::from_slice(&bytes)
+                .inspect_err(|e| {
+                    event!(Level::WARN, "Stored elections history not decodable: {}", e)
+                })
+                .unwrap_or_default(),
+            _ => HistoryState::default(),
+        };
+
+        Ok(Self {
+            state: Arc::new(RwLock::new(state)),
+            store,
+        })
+    }
+
+    /// All indexed terms, ordered by block number
+    pub fn terms(&self) -> Vec<ElectionTerm> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.terms.clone()
+    }
+
+    /// Last block processed by the indexer
+    pub fn indexed_block(&self) -> Option<u32> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.indexed_block
+    }
+
+    /// Mark blocks up to `block_number` as processed, persisting terms and events
+    /// indexed so far
+    pub fn set_indexed_block(&self, block_number: u32) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.indexed_block = Some(block_number);
+
+        if let Some(store) = &self.store {
+            let result = serde_json::to_vec(&*state)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| Ok(store.insert(HISTORY_KEY, bytes)?));
+            if let Err(e) = result {
+                event!(Level::WARN, "Error persisting elections history: {}", e);
+            }
+        }
+    }
+
+    /// Add new term (terms already indexed at the same block are replaced)
+    pub fn add_term(&self, term: ElectionTerm) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state
+            .terms
+            .retain(|existing| existing.block_number != term.block_number);
+        let position = state
+            .terms
+            .partition_point(|existing| existing.block_number < term.block_number);
+        state.terms.insert(position, term);
+    }
+
+    /// Attach events to the term during which they occured
+    pub fn add_events(&self, events: Vec<ElectionEvent>) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        for event in events {
+            match state
+                .terms
+                .iter_mut()
+                .rev()
+                .find(|term| term.block_number <= event.block_number)
+            {
+                Some(term) => term.events.push(event),
+                None => event!(
+                    Level::DEBUG,
+                    "Dropping election event before the first term: {:?}",
+                    event
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use super::*;
+use actix_web::web::Bytes;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+/// Latest elections data (default query) precomputed in the background,
+/// shared among all workers
+#[derive(Clone, Default)]
+pub struct LatestElections {
+    state: Arc<RwLock<Option<PrecomputedElections>>>,
+}
+
+#[derive(Clone)]
+pub struct PrecomputedElections {
+    /// Block hash the data was computed at
+    pub block_hash: Hash,
+    /// Time the data was computed at
+    pub computed_at: Instant,
+    /// Serialized elections data
+    pub body: Bytes,
+}
+
+impl LatestElections {
+    /// Most recently precomputed elections data, if any
+    pub fn get(&self) -> Option<PrecomputedElections> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.clone()
+    }
+
+    /// Replace precomputed elections data
+    pub fn set(&self, block_hash: Hash, body: Bytes) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        *state = Some(PrecomputedElections {
+            block_hash,
+            computed_at: Instant::now(),
+            body,
+        });
+    }
+}
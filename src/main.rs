@@ -1,28 +1,67 @@
 use crate::substrate::runtime_types::pallet_elections_phragmen::{SeatHolder, Voter};
-use actix_web::{App, HttpServer, Responder, error, get, web};
+use actix_web::{App, HttpServer, Responder, get, web};
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use sp_arithmetic::per_things::Perbill;
-use sp_npos_elections::{CandidatePtr, ElectionResult, PhragmenTrace};
+use sp_npos_elections::{
+    BalancingConfig, CandidatePtr, ElectionResult, ElectionScore, ExtendedBalance, PhragmenTrace,
+    PjrCounterExample, StakedAssignment, VoteWeight,
+};
+use std::path::PathBuf;
+use std::time::Duration;
 use subxt::{Config, OnlineClient, SubstrateConfig};
 use tracing::{Level, event};
 
 mod api;
 use api::*;
+mod blocks;
+use blocks::*;
+mod cache;
+use cache::*;
+mod error;
+use error::*;
+mod history;
+use history::*;
+mod network;
+use network::*;
+mod latest;
+use latest::*;
+mod mirror;
+use mirror::*;
 mod onchain;
 use onchain::*;
 mod phragmen;
 use phragmen::*;
+mod provider;
+use provider::*;
+mod runtime;
+use runtime::*;
+mod replay;
+use replay::*;
+mod rpc;
+use rpc::*;
+mod snapshot;
+use snapshot::*;
 mod traits;
 use traits::*;
+#[cfg(test)]
+mod tests;
 mod types;
 use types::api::*;
 use types::*;
 
 #[subxt::subxt(
     runtime_metadata_path = "./artifacts/mainnet.scale",
-    derive_for_all_types = "Clone"
+    derive_for_all_types = "Clone",
+    derive_for_type(
+        path = "pallet_elections_phragmen::SeatHolder",
+        derive = "serde::Serialize, serde::Deserialize"
+    ),
+    derive_for_type(
+        path = "pallet_elections_phragmen::Voter",
+        derive = "serde::Serialize, serde::Deserialize"
+    )
 )]
 pub mod substrate {}
 
@@ -30,13 +69,95 @@ pub mod substrate {}
 #[derive(Clone, Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// The node to connect to
-    #[arg(short, long, default_value = "wss://liberland-rpc.dwellir.com")]
-    url: String,
+    /// Network to connect to
+    #[arg(short, long, value_enum, default_value_t = Network::Mainnet)]
+    network: Network,
+
+    /// The nodes to connect to (in order of preference, others are used on failures),
+    /// defaults to the network's endpoints
+    #[arg(short, long = "url")]
+    urls: Vec<String>,
+
+    /// Timeout (in seconds) of a single RPC request
+    #[arg(long, default_value_t = 30)]
+    rpc_timeout: u64,
 
     /// Increase logging verbosity
     #[arg(short, long, default_value_t = false)]
     verbose: bool,
+
+    /// Time (in seconds) for which resolved identities are cached
+    #[arg(long, default_value_t = 600)]
+    identity_cache_ttl: u64,
+
+    /// Serve elections data from snapshots in this directory instead of the node
+    #[arg(long)]
+    snapshot_dir: Option<PathBuf>,
+
+    /// Keep latest elections storage mirrored locally instead of enumerating `Voting`
+    /// on every latest query
+    #[arg(long, default_value_t = false)]
+    mirror: bool,
+
+    /// Number of entries (elections data and results) kept in memory cache
+    #[arg(long, default_value_t = 64)]
+    cache_size: usize,
+
+    /// Persist cached elections data and results in this directory
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+
+    /// Number of entries kept in disk cache (least recently used ones are evicted)
+    #[arg(long, default_value_t = 1024)]
+    cache_disk_size: usize,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+// Commands other than running the server
+#[derive(Clone, Subcommand)]
+enum Command {
+    /// Export on-chain elections data snapshot to a file
+    Snapshot {
+        /// Block hash to take snapshot at (latest block if not set)
+        #[arg(short, long)]
+        block_hash: Option<Hash>,
+
+        /// Directory to write snapshot to
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+
+        /// Snapshot file format
+        #[arg(short, long, value_enum, default_value_t = SnapshotFormat::Scale)]
+        format: SnapshotFormat,
+    },
+    /// Compare simulated election with the one held on-chain (exits with 1 on mismatch)
+    Reconcile {
+        /// Block hash of the election
+        #[arg(short, long)]
+        block_hash: Hash,
+    },
+    /// Record JSON-RPC traffic of elections data request to a fixture file
+    Record {
+        /// Block hash to record elections data at
+        #[arg(short, long)]
+        block_hash: Hash,
+
+        /// Fixture file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Serve JSON-RPC requests recorded in a fixture file
+    Replay {
+        /// Fixture file to serve
+        #[arg(short, long)]
+        fixture: PathBuf,
+
+        /// Address to listen on
+        #[arg(short, long, default_value = "127.0.0.1:9944")]
+        listen: std::net::SocketAddr,
+    },
 }
 
 #[actix_web::main]
@@ -54,18 +175,189 @@ async fn main() -> std::io::Result<()> {
         .finish();
     tracing::subscriber::set_global_default(subscriber).expect("Default tracing subscriber error");
 
-    // Shared state
-    let onchain_data_provider: OnchainDataProvider<SubstrateConfig> =
-        OnchainDataProvider::new(&args.url)
+    let identity_cache_ttl = Duration::from_secs(args.identity_cache_ttl);
+    let rpc_timeout = Duration::from_secs(args.rpc_timeout);
+    let profile = args.network.profile();
+    profile.use_ss58_prefix();
+    let urls = profile.urls_or(&args.urls);
+
+    // Networks without default endpoints need `--url`, unless no node is used
+    let uses_node = match &args.command {
+        Some(Command::Replay { .. }) => false,
+        Some(_) => true,
+        None => args.snapshot_dir.is_none(),
+    };
+    if uses_node && urls.is_empty() {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                format!(
+                    "--url is required, the {} network has no default endpoints",
+                    profile.name
+                ),
+            )
+            .exit();
+    }
+
+    match &args.command {
+        // Export snapshot
+        Some(Command::Snapshot {
+            block_hash,
+            output,
+            format,
+        }) => {
+            let onchain_data_provider: OnchainDataProvider<SubstrateConfig> =
+                OnchainDataProvider::new(&urls, rpc_timeout, identity_cache_ttl)
+                    .await
+                    .expect("Error creating OnchainDataProvider");
+            export_snapshot(&onchain_data_provider, *block_hash, output, *format)
+                .await
+                .map_err(std::io::Error::other)?;
+            return Ok(());
+        }
+        // Reconcile simulation with on-chain election
+        Some(Command::Reconcile { block_hash }) => {
+            let onchain_data_provider: OnchainDataProvider<SubstrateConfig> =
+                OnchainDataProvider::new(&urls, rpc_timeout, identity_cache_ttl)
+                    .await
+                    .expect("Error creating OnchainDataProvider");
+            let reconciliation = reconcile_election_at(
+                &onchain_data_provider,
+                *block_hash,
+                &ApiElectionsQuery::default(),
+            )
             .await
-            .expect("Error creating OnchainDataProvider");
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+            let report = ApiReconciliation::build_from(&reconciliation);
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if !report.matches {
+                event!(
+                    Level::WARN,
+                    "Simulated election doesn't match on-chain election at block hash {:?}",
+                    block_hash
+                );
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        // Record fixture
+        Some(Command::Record { block_hash, output }) => {
+            let fixture = record_fixture(&urls, rpc_timeout, *block_hash)
+                .await
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            fixture
+                .save(output)
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            event!(
+                Level::INFO,
+                "Recorded {} requests to {}",
+                fixture.exchanges.len(),
+                output.display()
+            );
+            return Ok(());
+        }
+        // Serve recorded fixture
+        Some(Command::Replay { fixture, listen }) => {
+            let fixture =
+                RpcFixture::load(fixture).map_err(|e| std::io::Error::other(e.to_string()))?;
+            let _server = ReplayServer::start(&fixture.exchanges, *listen)
+                .await
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            tokio::signal::ctrl_c().await?;
+            return Ok(());
+        }
+        None => {}
+    }
+
+    // Shared state
+    let elections_cache = ElectionsCache::new(
+        args.cache_size,
+        args.cache_dir.as_deref(),
+        args.cache_disk_size,
+    )
+    .expect("Error creating ElectionsCache");
+    let history = ElectionsHistory::open(elections_cache.store().cloned())
+        .expect("Error loading ElectionsHistory");
+    let finalized_blocks = FinalizedBlocks::default();
+    let data_provider = match &args.snapshot_dir {
+        Some(dir) => DataProvider::Snapshot(
+            SnapshotDataProvider::new(dir).expect("Error creating SnapshotDataProvider"),
+        ),
+        None => {
+            let onchain_data_provider =
+                OnchainDataProvider::new(&urls, rpc_timeout, identity_cache_ttl)
+                    .await
+                    .expect("Error creating OnchainDataProvider");
+            onchain_data_provider.check_runtime(profile).await;
+
+            // Follow finalized blocks once for all their followers
+            actix_web::rt::spawn(
+                finalized_blocks
+                    .clone()
+                    .follow(onchain_data_provider.api().clone()),
+            );
+
+            // Carry latest identities over to new blocks until they change
+            actix_web::rt::spawn(
+                onchain_data_provider
+                    .clone()
+                    .follow_identity_changes(finalized_blocks.clone()),
+            );
+
+            // Index past elections and follow new ones
+            actix_web::rt::spawn(
+                onchain_data_provider
+                    .clone()
+                    .index_elections_history(history.clone(), finalized_blocks.clone()),
+            );
+
+            if args.mirror {
+                DataProvider::Mirror(MirrorDataProvider::new(onchain_data_provider))
+            } else {
+                DataProvider::Onchain(onchain_data_provider)
+            }
+        }
+    };
+    let data_provider = CachedDataProvider::new(data_provider, elections_cache);
+    let in_flight = ElectionsInFlight::default();
+    let latest = LatestElections::default();
+
+    // Precompute latest elections data whenever elections storage changes
+    // (once mirrored, if elections storage is mirrored)
+    let precompute = {
+        let data_provider = data_provider.clone();
+        let latest = latest.clone();
+        move |block_hash| {
+            precompute_latest_elections(data_provider.clone(), latest.clone(), block_hash)
+        }
+    };
+    match data_provider.provider() {
+        DataProvider::Onchain(onchain_data_provider) => {
+            actix_web::rt::spawn(
+                onchain_data_provider
+                    .clone()
+                    .follow_elections_changes(finalized_blocks, precompute),
+            );
+        }
+        DataProvider::Mirror(mirror_data_provider) => {
+            actix_web::rt::spawn(
+                mirror_data_provider
+                    .clone()
+                    .synchronise(finalized_blocks, precompute),
+            );
+        }
+        DataProvider::Snapshot(_) => {}
+    }
 
     // Start HTTP server
     HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(onchain_data_provider.clone()))
-            .service(council_elections_latest)
-            .service(council_elections_at_blockhash)
+            .app_data(web::Data::new(data_provider.clone()))
+            .app_data(web::Data::new(history.clone()))
+            .app_data(web::Data::new(in_flight.clone()))
+            .app_data(web::Data::new(latest.clone()))
+            .app_data(web::Data::new(args.network))
+            .configure(routes)
     })
     .workers(3)
     .bind(("0.0.0.0", 8080))?
@@ -0,0 +1,263 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use subxt::ext::codec::Encode;
+
+/// Data provider keeping a local copy of elections storage at latest finalized block,
+/// updated block by block, so that latest elections data needs no `Voting` enumeration.
+/// Data at other blocks is fetched from the node.
+#[derive(Clone)]
+pub struct MirrorDataProvider {
+    onchain: OnchainDataProvider<SubstrateConfig>,
+    state: Arc<RwLock<Option<MirrorState>>>,
+}
+
+struct MirrorState {
+    /// Elections data at mirrored block (`Voting` is kept in `voters`, its keys are
+    /// enumerated on the node at full synchronisation, then counted from fetched votes)
+    data: OnchainElectionsData,
+    /// Voters by their `Voting` storage key, ordered as in storage
+    voters: BTreeMap<Vec<u8>, (AccountId, Voter<AccountId, u128>)>,
+}
+
+impl MirrorDataProvider {
+    pub fn new(onchain: OnchainDataProvider<SubstrateConfig>) -> Self {
+        Self {
+            onchain,
+            state: Default::default(),
+        }
+    }
+
+    /// Keep mirroring elections storage as long as the node is reachable, calling
+    /// `on_change` with mirrored blocks at which elections storage changed
+    pub async fn synchronise<F, Fut>(self, finalized: FinalizedBlocks, mut on_change: F)
+    where
+        F: FnMut(Hash) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            if let Err(e) = self.follow_finalized(&finalized, &mut on_change).await {
+                event!(Level::WARN, "Error mirroring elections storage: {}", e);
+            }
+            actix_web::rt::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    /// Mirror latest finalized block in full, then apply changes of following blocks.
+    /// Changes are reported once mirrored, so that their elections data is served
+    /// from the mirror.
+    async fn follow_finalized<F, Fut>(
+        &self,
+        finalized: &FinalizedBlocks,
+        on_change: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(Hash) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        // Subscribe first, so that no blocks are missed during full synchronisation
+        let mut blocks = finalized.subscribe();
+        let finalized = self
+            .onchain
+            .latest_blockhash(LatestBlock::Finalized)
+            .await?;
+        let mut mirrored_block = self.synchronise_full(finalized).await?;
+        on_change(finalized).await;
+
+        while let Some(block) = blocks.next().await {
+            if block.number <= mirrored_block {
+                continue;
+            }
+
+            // Missed blocks can't be diffed, so the whole storage is mirrored again
+            let changes = if block.number == mirrored_block + 1 {
+                self.onchain.voting_changes(&block)?
+            } else {
+                VotingChanges::Unknown
+            };
+            let changed = match changes {
+                VotingChanges::Accounts(accounts) => {
+                    self.synchronise_accounts(block.hash, &accounts).await?
+                }
+                VotingChanges::Unknown => {
+                    self.synchronise_full(block.hash).await?;
+                    true
+                }
+            };
+            mirrored_block = block.number;
+
+            if changed {
+                event!(
+                    Level::DEBUG,
+                    "Elections storage changed at block #{} ({:?})",
+                    block.number,
+                    block.hash
+                );
+                on_change(block.hash).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Mirror whole elections storage at given block
+    async fn synchronise_full(&self, block_hash: Hash) -> Result<u32> {
+        let mut data = self
+            .onchain
+            .elections_at_blockhash(Some(block_hash))
+            .await?;
+        let mut voters = BTreeMap::new();
+        for (account, voter) in std::mem::take(&mut data.voting) {
+            voters.insert(self.onchain.voting_key(&account)?, (account, voter));
+        }
+        let block_number = data.block_number;
+        event!(
+            Level::INFO,
+            "Mirrored elections storage at block #{}: {} voters",
+            block_number,
+            voters.len()
+        );
+
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        *state = Some(MirrorState { data, voters });
+
+        Ok(block_number)
+    }
+
+    /// Update mirror to given block, in which only votes of `accounts` may have changed.
+    /// Returns whether votes or seats changed.
+    async fn synchronise_accounts(&self, block_hash: Hash, accounts: &[AccountId]) -> Result<bool> {
+        let runtime = self.onchain.runtime_at(block_hash).await?;
+        let data = self
+            .onchain
+            .elections_summary_at(&runtime, block_hash)
+            .await?;
+        let voters = self
+            .onchain
+            .fetch_voters(&runtime, accounts, block_hash)
+            .await?;
+
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        let state = state
+            .as_mut()
+            .ok_or_else(|| Error::Internal("Elections storage not mirrored".to_string()))?;
+        let mut changed = seats(&state.data) != seats(&data);
+        // Keys enumerated at full synchronisation, adjusted for keys inserted or removed since
+        let mut voting_integrity = state.data.voting_integrity;
+        for (account, voter) in voters {
+            let key = self.onchain.voting_key(&account)?;
+            let encoded = voter.as_ref().map(Encode::encode);
+            let previous = match voter {
+                Some(voter) => state.voters.insert(key, (account, voter)),
+                None => state.voters.remove(&key),
+            };
+            changed |= previous.as_ref().map(|(_, voter)| voter.encode()) != encoded;
+            if let Some(integrity) = voting_integrity.as_mut() {
+                match (previous.is_some(), encoded.is_some()) {
+                    (false, true) => integrity.keys += 1,
+                    (true, false) => integrity.keys = integrity.keys.saturating_sub(1),
+                    _ => {}
+                }
+            }
+        }
+        if !accounts.is_empty() {
+            event!(
+                Level::DEBUG,
+                "Mirrored votes of {} accounts at block #{}",
+                accounts.len(),
+                data.block_number
+            );
+        }
+        state.data = OnchainElectionsData {
+            voting_integrity,
+            ..data
+        };
+
+        Ok(changed)
+    }
+
+    /// Mirrored elections data, if mirror is at given block hash (latest if none)
+    fn mirrored_at(&self, hash: Option<Hash>) -> Option<OnchainElectionsData> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = state
+            .as_ref()
+            .filter(|state| hash.is_none_or(|hash| hash == state.data.block_hash))?;
+
+        let voting: Vec<_> = state.voters.values().cloned().collect();
+        Some(OnchainElectionsData {
+            block_hash: state.data.block_hash,
+            desired_members: state.data.desired_members,
+            desired_runners_up: state.data.desired_runners_up,
+            election_rounds: state.data.election_rounds,
+            total_issuance: state.data.total_issuance,
+            members: state.data.members.clone(),
+            runners_up: state.data.runners_up.clone(),
+            candidates: state.data.candidates.clone(),
+            voting_integrity: state.data.voting_integrity,
+            voting,
+            block_number: state.data.block_number,
+            timestamp: state.data.timestamp,
+        })
+    }
+}
+
+/// Encoded seats and candidates, whose changes are reported along with changed votes
+fn seats(data: &OnchainElectionsData) -> Vec<u8> {
+    (
+        data.election_rounds,
+        &data.members,
+        &data.runners_up,
+        &data.candidates,
+    )
+        .encode()
+}
+
+impl OnchainElectionsDataProvider for MirrorDataProvider {
+    async fn elections_at_blockhash(&self, hash: Option<Hash>) -> Result<OnchainElectionsData> {
+        match self.mirrored_at(hash) {
+            Some(data) => {
+                event!(
+                    Level::DEBUG,
+                    "Using mirrored election data at block hash: {:?}",
+                    data.block_hash
+                );
+                Ok(data)
+            }
+            None => self.onchain.elections_at_blockhash(hash).await,
+        }
+    }
+
+    async fn parent_blockhash(&self, hash: Hash) -> Result<Hash> {
+        self.onchain.parent_blockhash(hash).await
+    }
+
+    async fn blockhash_at(&self, number: u32) -> Result<Hash> {
+        self.onchain.blockhash_at(number).await
+    }
+
+    async fn latest_blockhash(&self, latest: LatestBlock) -> Result<Hash> {
+        // Latest finalized block is the mirrored one, until mirror catches up
+        if latest == LatestBlock::Finalized {
+            let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(state) = state.as_ref() {
+                return Ok(state.data.block_hash);
+            }
+        }
+
+        self.onchain.latest_blockhash(latest).await
+    }
+}
+
+impl OnchainIdentityProvider for MirrorDataProvider {
+    async fn map_elections_identities(
+        &self,
+        elections: &mut ApiElectionData,
+        at: Hash,
+        include_current: bool,
+    ) -> Result<()> {
+        self.onchain
+            .map_elections_identities(elections, at, include_current)
+            .await
+    }
+}
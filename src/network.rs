@@ -0,0 +1,95 @@
+use super::*;
+use sp_core::crypto::{Ss58AddressFormat, Ss58Codec};
+use std::sync::OnceLock;
+
+/// SS58 prefix of addresses returned by the API (set on startup)
+static SS58_PREFIX: OnceLock<u16> = OnceLock::new();
+
+/// Networks the tool can be run against
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Network {
+    /// Liberland mainnet
+    #[default]
+    Mainnet,
+    /// Bastiat testnet
+    Bastiat,
+    /// Local development chain (`--dev` node)
+    Dev,
+}
+
+/// Settings of a network. Storage of every network is decoded with the metadata of
+/// each block's runtime, so profiles carry no metadata artifacts of their own.
+#[derive(Debug)]
+pub struct NetworkProfile {
+    pub name: &'static str,
+    /// RPC endpoints used if no `--url` is given
+    pub urls: &'static [&'static str],
+    pub ss58_prefix: u16,
+    pub token_symbol: &'static str,
+    pub token_decimals: u8,
+}
+
+const MAINNET: NetworkProfile = NetworkProfile {
+    name: "mainnet",
+    urls: &["wss://liberland-rpc.dwellir.com"],
+    ss58_prefix: 42,
+    token_symbol: "LLD",
+    token_decimals: 12,
+};
+
+// No public endpoint yet, `--url` is required. Testnet and dev chain specs share mainnet's
+// properties (checked against the node on startup).
+const BASTIAT: NetworkProfile = NetworkProfile {
+    name: "bastiat",
+    urls: &[],
+    ss58_prefix: 42,
+    token_symbol: "LLD",
+    token_decimals: 12,
+};
+
+const DEV: NetworkProfile = NetworkProfile {
+    name: "dev",
+    urls: &["ws://127.0.0.1:9944"],
+    ss58_prefix: 42,
+    token_symbol: "LLD",
+    token_decimals: 12,
+};
+
+impl Network {
+    pub fn profile(&self) -> &'static NetworkProfile {
+        match self {
+            Network::Mainnet => &MAINNET,
+            Network::Bastiat => &BASTIAT,
+            Network::Dev => &DEV,
+        }
+    }
+}
+
+impl NetworkProfile {
+    /// RPC endpoints to connect to (given ones, or the profile's defaults)
+    pub fn urls_or(&self, urls: &[String]) -> Vec<String> {
+        if !urls.is_empty() {
+            return urls.to_vec();
+        }
+
+        self.urls.iter().map(|url| url.to_string()).collect()
+    }
+
+    /// Format addresses returned by the API with this network's SS58 prefix
+    pub fn use_ss58_prefix(&self) {
+        if SS58_PREFIX.set(self.ss58_prefix).is_err() {
+            event!(Level::WARN, "SS58 prefix of addresses already set");
+        }
+    }
+}
+
+/// SS58 address of account, with the prefix of the network in use
+pub fn ss58_address(account: &AccountId) -> String {
+    let prefix = SS58_PREFIX
+        .get()
+        .copied()
+        .unwrap_or(Network::default().profile().ss58_prefix);
+
+    sp_core::crypto::AccountId32::from(account.0)
+        .to_ss58check_with_version(Ss58AddressFormat::custom(prefix))
+}
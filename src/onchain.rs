@@ -1,17 +1,682 @@
 use super::*;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use subxt::backend::legacy::LegacyRpcMethods;
+use subxt::backend::rpc::{RpcClient, rpc_params};
+use subxt::error::RpcError;
+use subxt::ext::codec::Decode;
+use subxt::ext::scale_value::{Composite, Primitive, Value, ValueDef};
+
+/// Maximum number of storage keys requested in a single query
+const STORAGE_QUERY_CHUNK_SIZE: usize = 256;
+/// Number of keys requested per page when enumerating storage
+const STORAGE_PAGE_SIZE: u32 = 1000;
+/// Number of times a failed storage request is retried
+const STORAGE_RETRIES: u32 = 3;
+/// Pallets whose calls neither change votes nor dispatch other calls
+const VOTING_NEUTRAL_PALLETS: &[&str] = &[
+    "Timestamp",
+    "Balances",
+    "Identity",
+    "Session",
+    "Staking",
+    "ImOnline",
+    "Babe",
+    "Grandpa",
+    "ElectionProviderMultiPhase",
+    "VoterList",
+    "Vesting",
+    "Assets",
+    "Nfts",
+    "Preimage",
+];
+
+/// Identities cached by block hash and account (None if account has no identity).
+/// Identities at a block never change, so entries only expire to bound memory use.
+pub type IdentityCache = TtlCache<(Hash, AccountId), Option<ApiIdentity>>;
+
+/// Identities at the latest followed finalized block, by account. Entries carry over to
+/// following blocks until an `Identity` event names the account or its parent, so that
+/// latest elections don't query the same identities again on every block.
+#[derive(Clone)]
+pub struct LatestIdentities {
+    /// Number and hash of the latest followed block (None until a block is followed)
+    block: Arc<Mutex<Option<(u32, Hash)>>>,
+    entries: TtlCache<AccountId, Option<ApiIdentity>>,
+}
+
+impl LatestIdentities {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            block: Default::default(),
+            entries: TtlCache::new(ttl),
+        }
+    }
+
+    /// Hash of the latest followed block
+    pub fn block_hash(&self) -> Option<Hash> {
+        let block = self.block.lock().unwrap_or_else(|e| e.into_inner());
+        (*block).map(|(_, hash)| hash)
+    }
+
+    /// Identity of account, if cached and `at` is the latest followed block
+    pub fn get(&self, at: Hash, account: &AccountId) -> Option<Option<ApiIdentity>> {
+        let block = self.block.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(*block, Some((_, hash)) if hash == at) {
+            self.entries.get(account)
+        } else {
+            None
+        }
+    }
+
+    /// Cache identity resolved at `at`, if it is the latest followed block
+    pub fn insert(&self, at: Hash, account: AccountId, identity: Option<ApiIdentity>) {
+        let block = self.block.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(*block, Some((_, hash)) if hash == at) {
+            self.entries.insert(account, identity);
+        }
+    }
+
+    /// Move to next followed block, dropping identities of `changed` accounts and of their
+    /// sub-accounts. All identities are dropped if blocks were skipped.
+    pub fn advance(&self, number: u32, hash: Hash, changed: &[AccountId]) {
+        let mut block = self.block.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(*block, Some((previous, _)) if previous + 1 == number) {
+            let parents: Vec<String> = changed.iter().map(ss58_address).collect();
+            self.entries.invalidate(|account, identity| {
+                let is_sub = identity
+                    .as_ref()
+                    .and_then(|identity| identity.parent.as_ref())
+                    .is_some_and(|parent| parents.contains(&parent.address));
+                changed.contains(account) || is_sub
+            });
+        } else {
+            self.entries.invalidate(|_, _| true);
+        }
+        *block = Some((number, hash));
+    }
+}
 
 #[derive(Clone)]
 pub struct OnchainDataProvider<C: Config> {
     api: OnlineClient<C>,
+    rpc_client: RpcClient,
+    rpc: LegacyRpcMethods<C>,
+    identities: IdentityCache,
+    latest_identities: LatestIdentities,
+    /// Clients decoding storage of runtimes seen so far, by spec version
+    runtimes: Arc<Mutex<HashMap<u32, RuntimeClient>>>,
 }
 
 impl<C: Config> OnchainDataProvider<C> {
-    pub async fn new(uri: &str) -> Result<Self> {
-        let api = OnlineClient::<C>::from_url(uri).await?;
+    pub async fn new(
+        urls: &[String],
+        rpc_timeout: Duration,
+        identity_cache_ttl: Duration,
+    ) -> Result<Self> {
+        let rpc_client = RpcClient::new(FailoverRpcClient::new(urls, rpc_timeout)?);
+        Self::from_rpc_client(rpc_client, identity_cache_ttl).await
+    }
+
+    pub async fn from_rpc_client(
+        rpc_client: RpcClient,
+        identity_cache_ttl: Duration,
+    ) -> Result<Self> {
+        let api = OnlineClient::<C>::from_rpc_client(rpc_client.clone()).await?;
+        let rpc = LegacyRpcMethods::<C>::new(rpc_client.clone());
+
+        Ok(Self {
+            api,
+            rpc_client,
+            rpc,
+            identities: IdentityCache::new(identity_cache_ttl),
+            latest_identities: LatestIdentities::new(identity_cache_ttl),
+            runtimes: Default::default(),
+        })
+    }
+}
+
+impl OnchainDataProvider<SubstrateConfig> {
+    /// Compare runtime and properties of the node with the network profile
+    pub async fn check_runtime(&self, profile: &NetworkProfile) {
+        let spec_version = self.api.runtime_version().spec_version();
+        let metadata_hash = self.api.metadata().hasher().hash();
+        let bundled = bundled_runtime();
+        if metadata_hash == bundled.metadata_hash {
+            event!(
+                Level::INFO,
+                "Node runtime (spec version {}) matches bundled metadata",
+                spec_version
+            );
+        } else {
+            event!(
+                Level::WARN,
+                "Node runtime (spec version {}, metadata hash 0x{}) differs from bundled metadata (spec version {}, metadata hash 0x{}), storage will be decoded dynamically",
+                spec_version,
+                hex::encode(metadata_hash),
+                bundled.spec_version,
+                hex::encode(bundled.metadata_hash)
+            );
+        }
+
+        let properties = match self.rpc.system_properties().await {
+            Ok(properties) => properties,
+            Err(e) => {
+                event!(Level::WARN, "Error fetching node properties: {}", e);
+                return;
+            }
+        };
+        // Chains with several tokens report lists, the native token being the first
+        let first = |value: Option<&serde_json::Value>| match value {
+            Some(serde_json::Value::Array(values)) => values.first().cloned(),
+            value => value.cloned(),
+        };
+        let ss58_prefix = properties.get("ss58Format").and_then(|v| v.as_u64());
+        let token_symbol = first(properties.get("tokenSymbol"));
+        let token_decimals = first(properties.get("tokenDecimals")).and_then(|v| v.as_u64());
+        if ss58_prefix.is_some_and(|prefix| prefix != profile.ss58_prefix as u64)
+            || token_symbol
+                .as_ref()
+                .and_then(|v| v.as_str())
+                .is_some_and(|symbol| symbol != profile.token_symbol)
+            || token_decimals.is_some_and(|decimals| decimals != profile.token_decimals as u64)
+        {
+            event!(
+                Level::WARN,
+                "Node properties (SS58 prefix {:?}, token {:?} with {:?} decimals) differ from {} network profile",
+                ss58_prefix,
+                token_symbol,
+                token_decimals,
+                profile.name
+            );
+        }
+    }
+
+    /// Client decoding storage at given block, with metadata of the block's runtime
+    pub async fn runtime_at(&self, block_hash: Hash) -> Result<RuntimeClient> {
+        // Unknown blocks are reported as such, rather than as runtime errors
+        if self.rpc.chain_get_header(Some(block_hash)).await?.is_none() {
+            return Err(Error::BlockNotFound(format!("{:?}", block_hash)).into());
+        }
+
+        let version = self.rpc.state_get_runtime_version(Some(block_hash)).await?;
+        let cached = {
+            let runtimes = self.runtimes.lock().unwrap_or_else(|e| e.into_inner());
+            runtimes.get(&version.spec_version).cloned()
+        };
+        if let Some(runtime) = cached {
+            return Ok(runtime);
+        }
+
+        // Runtimes matching the bundled metadata are decoded with generated types
+        let bundled = bundled_runtime();
+        let metadata = if version.spec_version == bundled.spec_version {
+            bundled.metadata.clone()
+        } else {
+            self.rpc.state_get_metadata(Some(block_hash)).await?
+        };
+        let compatible = metadata.hasher().hash() == bundled.metadata_hash;
+        if !compatible {
+            event!(
+                Level::WARN,
+                "Runtime spec version {} (block hash {:?}) differs from bundled metadata, decoding storage dynamically",
+                version.spec_version,
+                block_hash
+            );
+        }
+        let runtime = RuntimeClient {
+            api: OnlineClient::from_rpc_client_with(
+                self.api.genesis_hash(),
+                subxt::client::RuntimeVersion::new(
+                    version.spec_version,
+                    version.transaction_version,
+                ),
+                metadata,
+                self.rpc_client.clone(),
+            )?,
+            compatible,
+        };
+
+        let mut runtimes = self.runtimes.lock().unwrap_or_else(|e| e.into_inner());
+        runtimes.insert(version.spec_version, runtime.clone());
+
+        Ok(runtime)
+    }
+
+    /// Keep indexing elections history as long as the node is reachable
+    pub async fn index_elections_history(
+        self,
+        history: ElectionsHistory,
+        finalized: FinalizedBlocks,
+    ) {
+        loop {
+            if let Err(e) = self.index_elections(&history, &finalized).await {
+                event!(Level::WARN, "Error indexing elections history: {}", e);
+            }
+            actix_web::rt::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    /// Index past elections, then follow finalized blocks.
+    ///
+    /// Past elections are found by binary search on `ElectionRounds` (incremented by every
+    /// successful election), so that elections held off the current `TermDuration` schedule
+    /// are found as well. Failed elections leave no trace in storage, so they are looked
+    /// for only at blocks scheduled by current `TermDuration`. Events emitted in other
+    /// blocks (e.g. `Renounced`) are indexed only from now on.
+    async fn index_elections(
+        &self,
+        history: &ElectionsHistory,
+        finalized: &FinalizedBlocks,
+    ) -> Result<()> {
+        // Subscribe first, so that no blocks are missed during backfill
+        let mut blocks = finalized.subscribe();
+        let finalized_hash = self.latest_blockhash(LatestBlock::Finalized).await?;
+        let finalized = self.api.blocks().at(finalized_hash).await?.number();
+
+        let first_block = history.indexed_block().map_or(1, |block| block + 1);
+        if first_block <= finalized {
+            let mut election_blocks = self.election_round_blocks(first_block, finalized).await?;
+            let term_duration = self
+                .api
+                .constants()
+                .at(&substrate::constants().elections().term_duration())?;
+            if term_duration > 0 {
+                let first_term_block = first_block.div_ceil(term_duration) * term_duration;
+                election_blocks
+                    .extend((first_term_block..=finalized).step_by(term_duration as usize));
+            }
+            election_blocks.sort_unstable();
+            election_blocks.dedup();
+
+            for block_number in election_blocks {
+                self.index_elections_block(history, block_number).await?;
+                history.set_indexed_block(block_number);
+            }
+            history.set_indexed_block(finalized);
+        }
+        event!(
+            Level::INFO,
+            "Elections history indexed up to block {}: {} terms",
+            finalized,
+            history.terms().len()
+        );
+
+        while let Some(block) = blocks.next().await {
+            // Blocks missed in between are fetched from the node
+            let first_block = history.indexed_block().map_or(1, |block| block + 1);
+            for block_number in first_block..block.number {
+                self.index_elections_block(history, block_number).await?;
+                history.set_indexed_block(block_number);
+            }
+            if block.number >= first_block {
+                self.index_elections_events(history, block.number, block.hash, &block.events)
+                    .await?;
+                history.set_indexed_block(block.number);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Blocks within `first_block..=last_block` at which `ElectionRounds` was incremented
+    async fn election_round_blocks(&self, first_block: u32, last_block: u32) -> Result<Vec<u32>> {
+        let first_round = self
+            .election_rounds_at(first_block.saturating_sub(1))
+            .await?
+            + 1;
+        let last_round = self.election_rounds_at(last_block).await?;
+
+        let mut blocks = vec![];
+        let mut low = first_block;
+        for round in first_round..=last_round {
+            // First block at which `round` was reached
+            let mut high = last_block;
+            while low < high {
+                let middle = low + (high - low) / 2;
+                if self.election_rounds_at(middle).await? >= round {
+                    high = middle;
+                } else {
+                    low = middle + 1;
+                }
+            }
+            blocks.push(low);
+        }
+
+        Ok(blocks)
+    }
+
+    /// Value of `ElectionRounds` at given block number
+    async fn election_rounds_at(&self, block_number: u32) -> Result<u32> {
+        let block_hash = self.blockhash_at(block_number).await?;
+        let election_rounds = self
+            .api
+            .storage()
+            .at(block_hash)
+            .fetch_or_default(&substrate::storage().elections().election_rounds())
+            .await?;
+
+        Ok(election_rounds)
+    }
+
+    /// Keep calling `on_change` with finalized blocks at which elections storage
+    /// may have changed
+    pub async fn follow_elections_changes<F, Fut>(
+        self,
+        finalized: FinalizedBlocks,
+        mut on_change: F,
+    ) where
+        F: FnMut(Hash) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            if let Err(e) = self
+                .watch_elections_changes(&finalized, &mut on_change)
+                .await
+            {
+                event!(Level::WARN, "Error watching elections changes: {}", e);
+            }
+            actix_web::rt::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    /// Follow finalized blocks and report those at which `Candidates`, `Members` or
+    /// `RunnersUp` storage hashes changed, the elections pallet emitted any event, or
+    /// votes may have changed. Blocks following missed ones are always reported.
+    ///
+    /// `Voting` is a map without a storage hash of its own, and it is too large to compare
+    /// on every block, so it is considered changed whenever the block may have changed
+    /// any votes (see `voting_changes`).
+    async fn watch_elections_changes<F, Fut>(
+        &self,
+        finalized: &FinalizedBlocks,
+        on_change: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(Hash) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let keys = [
+            self.api
+                .storage()
+                .address_bytes(&substrate::storage().elections().candidates())?,
+            self.api
+                .storage()
+                .address_bytes(&substrate::storage().elections().members())?,
+            self.api
+                .storage()
+                .address_bytes(&substrate::storage().elections().runners_up())?,
+        ];
+
+        let mut blocks = finalized.subscribe();
+        let mut previous: Option<(u32, Vec<Option<Hash>>)> = None;
+        while let Some(block) = blocks.next().await {
+            let mut storage_hashes = vec![];
+            for key in &keys {
+                let storage_hash: Option<Hash> = self
+                    .rpc_client
+                    .request(
+                        "state_getStorageHash",
+                        rpc_params![format!("0x{}", hex::encode(key)), block.hash],
+                    )
+                    .await?;
+                storage_hashes.push(storage_hash);
+            }
+
+            let mut elections_event = false;
+            for event in block.events.iter() {
+                elections_event |= event?.pallet_name() == "Elections";
+            }
+
+            let changed = previous.as_ref().is_none_or(|(number, hashes)| {
+                *number + 1 != block.number || *hashes != storage_hashes
+            }) || elections_event
+                || !matches!(
+                    self.voting_changes(&block)?,
+                    VotingChanges::Accounts(accounts) if accounts.is_empty()
+                );
+            previous = Some((block.number, storage_hashes));
+
+            if changed {
+                event!(
+                    Level::DEBUG,
+                    "Elections storage changed at block #{} ({:?})",
+                    block.number,
+                    block.hash
+                );
+                on_change(block.hash).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keep carrying latest identities over to new finalized blocks
+    pub async fn follow_identity_changes(self, finalized: FinalizedBlocks) {
+        loop {
+            if let Err(e) = self.watch_identity_changes(&finalized).await {
+                event!(Level::WARN, "Error watching identity changes: {}", e);
+            }
+            actix_web::rt::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    /// Follow finalized blocks, invalidating latest identities of accounts named in
+    /// `Identity` pallet events
+    async fn watch_identity_changes(&self, finalized: &FinalizedBlocks) -> Result<()> {
+        let mut blocks = finalized.subscribe();
+        while let Some(block) = blocks.next().await {
+            let mut changed = vec![];
+            for event in block.events.iter() {
+                let event = event?;
+                if event.pallet_name() != "Identity" {
+                    continue;
+                }
+                changed.extend(event.field_values()?.values().filter_map(call_account));
+            }
+            if !changed.is_empty() {
+                event!(
+                    Level::DEBUG,
+                    "Identities of {} accounts changed at block #{}",
+                    changed.len(),
+                    block.number
+                );
+            }
+            self.latest_identities
+                .advance(block.number, block.hash, &changed);
+        }
+
+        Ok(())
+    }
+
+    /// Client used to follow blocks
+    pub fn api(&self) -> &OnlineClient<SubstrateConfig> {
+        &self.api
+    }
+
+    /// Accounts whose votes may have changed in given block.
+    ///
+    /// Every extrinsic is classified by its call (see `call_voting_changes`). Changes of
+    /// blocks with unclassified extrinsics or scheduled calls can't be attributed to
+    /// accounts.
+    pub fn voting_changes(&self, block: &FinalizedBlock) -> Result<VotingChanges> {
+        for event in block.events.iter() {
+            let event = event?;
+            if event.pallet_name() == "Scheduler" && event.variant_name() == "Dispatched" {
+                return Ok(VotingChanges::Unknown);
+            }
+        }
+
+        let mut accounts = vec![];
+        for extrinsic in block.extrinsics.iter() {
+            let signer = match extrinsic.address_bytes() {
+                Some(mut bytes) => match <SubstrateConfig as Config>::Address::decode(&mut bytes) {
+                    Ok(subxt::utils::MultiAddress::Id(account)) => Some(account),
+                    _ => return Ok(VotingChanges::Unknown),
+                },
+                // Inherents and unsigned extrinsics
+                None => None,
+            };
+            let classified = call_voting_changes(
+                extrinsic.pallet_name()?,
+                extrinsic.variant_name()?,
+                &extrinsic.field_values()?,
+                signer.as_ref(),
+                &mut accounts,
+            );
+            if !classified {
+                return Ok(VotingChanges::Unknown);
+            }
+        }
+        accounts.sort_unstable();
+        accounts.dedup();
+
+        Ok(VotingChanges::Accounts(accounts))
+    }
+
+    /// `Voting` storage key of given account (voters are enumerated in key order)
+    pub fn voting_key(&self, account: &AccountId) -> Result<Vec<u8>> {
+        let key = self
+            .api
+            .storage()
+            .address_bytes(&substrate::storage().elections().voting(account))?;
+
+        Ok(key)
+    }
+
+    /// Fetch votes of given accounts (None for accounts which don't vote)
+    pub async fn fetch_voters(
+        &self,
+        runtime: &RuntimeClient,
+        accounts: &[AccountId],
+        block_hash: Hash,
+    ) -> Result<Vec<(AccountId, Option<Voter<AccountId, u128>>)>> {
+        let keys = accounts
+            .iter()
+            .map(|account| self.voting_key(account))
+            .collect::<Result<Vec<_>>>()?;
+        let mut values = self
+            .fetch_storage_values(&keys, Some(block_hash), &mut 0)
+            .await?;
+
+        let mut voters = Vec::with_capacity(accounts.len());
+        for (account, key) in accounts.iter().zip(&keys) {
+            let voter = values
+                .remove(key)
+                .map(|value| runtime.decode_value("Elections", "Voting", &value))
+                .transpose()?;
+            voters.push((account.clone(), voter));
+        }
+
+        Ok(voters)
+    }
+
+    /// Index elections pallet events emitted in given block
+    async fn index_elections_block(
+        &self,
+        history: &ElectionsHistory,
+        block_number: u32,
+    ) -> Result<()> {
+        let block_hash = self.blockhash_at(block_number).await?;
+        let events = self.api.events().at(block_hash).await?;
+
+        self.index_elections_events(history, block_number, block_hash, &events)
+            .await
+    }
+
+    /// Index elections pallet events emitted in block with given number and hash
+    async fn index_elections_events(
+        &self,
+        history: &ElectionsHistory,
+        block_number: u32,
+        block_hash: Hash,
+        events: &subxt::events::Events<SubstrateConfig>,
+    ) -> Result<()> {
+        use substrate::elections::events::{
+            CandidateSlashed, ElectionError, EmptyTerm, MemberKicked, NewTerm, Renounced,
+            SeatHolderSlashed,
+        };
+
+        let mut outcome = None;
+        let mut members = vec![];
+        let mut election_events = vec![];
+        for event in events.iter() {
+            let event = event?;
+            if event.pallet_name() != "Elections" {
+                continue;
+            }
+
+            if let Some(e) = event.as_event::<NewTerm>()? {
+                outcome = Some(TermOutcome::NewTerm);
+                members = e.new_members;
+                continue;
+            } else if event.as_event::<EmptyTerm>()?.is_some() {
+                outcome = Some(TermOutcome::EmptyTerm);
+                continue;
+            } else if event.as_event::<ElectionError>()?.is_some() {
+                outcome = Some(TermOutcome::ElectionError);
+                continue;
+            }
+
+            let (kind, account, amount) = if let Some(e) = event.as_event::<MemberKicked>()? {
+                (ElectionEventKind::MemberKicked, e.member, None)
+            } else if let Some(e) = event.as_event::<Renounced>()? {
+                (ElectionEventKind::Renounced, e.candidate, None)
+            } else if let Some(e) = event.as_event::<CandidateSlashed>()? {
+                (
+                    ElectionEventKind::CandidateSlashed,
+                    e.candidate,
+                    Some(e.amount),
+                )
+            } else if let Some(e) = event.as_event::<SeatHolderSlashed>()? {
+                (
+                    ElectionEventKind::SeatHolderSlashed,
+                    e.seat_holder,
+                    Some(e.amount),
+                )
+            } else {
+                continue;
+            };
+            election_events.push(ElectionEvent {
+                block_number,
+                kind,
+                account,
+                amount,
+            });
+        }
+
+        if let Some(outcome) = outcome {
+            let storage = self.api.storage().at(block_hash);
+            let timestamp = storage
+                .fetch_or_default(&substrate::storage().timestamp().now())
+                .await?;
+            let election_rounds = storage
+                .fetch_or_default(&substrate::storage().elections().election_rounds())
+                .await?;
+            event!(
+                Level::DEBUG,
+                "Indexed election #{} at block #{}: {:?}",
+                election_rounds,
+                block_number,
+                outcome
+            );
 
-        Ok(Self { api })
+            history.add_term(ElectionTerm {
+                block_number,
+                block_hash,
+                timestamp,
+                election_rounds,
+                outcome,
+                members,
+                events: vec![],
+            });
+        }
+        history.add_events(election_events);
+
+        Ok(())
     }
 }
 
@@ -28,7 +693,7 @@ impl OnchainElectionsDataProvider for OnchainDataProvider<SubstrateConfig> {
                 hash
             }
             None => {
-                let latest_block_hash = self.api.blocks().at_latest().await?.hash();
+                let latest_block_hash = self.latest_blockhash(LatestBlock::Finalized).await?;
                 event!(
                     Level::DEBUG,
                     "Fetching election data at block hash: {:?} (latest)",
@@ -38,174 +703,838 @@ impl OnchainElectionsDataProvider for OnchainDataProvider<SubstrateConfig> {
             }
         };
 
-        // Fetch constants: DesiredMembers, DesiredRunnersUp
-        let desired_members = self
-            .api
-            .constants()
-            .at(&substrate::constants().elections().desired_members())?;
-        let desired_runners_up = self
-            .api
-            .constants()
-            .at(&substrate::constants().elections().desired_runners_up())?;
+        let runtime = self.runtime_at(block_hash).await?;
+        let mut data = self.elections_summary_at(&runtime, block_hash).await?;
+
+        // Fetch Voting
+        let (voting, voting_integrity) = self.fetch_voting(&runtime, block_hash).await?;
+        data.voting = voting;
+        data.voting_integrity = Some(voting_integrity);
+
+        Ok(data)
+    }
+
+    async fn parent_blockhash(&self, hash: Hash) -> Result<Hash> {
+        let block = self.api.blocks().at(hash).await?;
+
+        Ok(block.header().parent_hash)
+    }
+
+    async fn blockhash_at(&self, number: u32) -> Result<Hash> {
+        self.rpc
+            .chain_get_block_hash(Some(number.into()))
+            .await?
+            .ok_or_else(|| Error::BlockNotFound(format!("#{}", number)).into())
+    }
+
+    async fn latest_blockhash(&self, latest: LatestBlock) -> Result<Hash> {
+        let hash = match latest {
+            LatestBlock::Finalized => self.rpc.chain_get_finalized_head().await?,
+            LatestBlock::Best => self
+                .rpc
+                .chain_get_block_hash(None)
+                .await?
+                .ok_or_else(|| Error::BlockNotFound("best".to_string()))?,
+        };
 
-        // Fetch Elections data
-        let storage = self.api.storage().at(block_hash);
+        Ok(hash)
+    }
+}
+
+impl OnchainDataProvider<SubstrateConfig> {
+    /// Fetch elections data at block hash, except for `Voting` (which is left empty)
+    pub async fn elections_summary_at(
+        &self,
+        runtime: &RuntimeClient,
+        block_hash: Hash,
+    ) -> Result<OnchainElectionsData> {
+        let block_number = runtime.api.blocks().at(block_hash).await?.number();
+
+        // Fetch constants: DesiredMembers, DesiredRunnersUp
+        let desired_members =
+            runtime.constant(&substrate::constants().elections().desired_members())?;
+        let desired_runners_up =
+            runtime.constant(&substrate::constants().elections().desired_runners_up())?;
 
         // Fetch number of election rounds
-        let election_rounds = storage
-            .fetch(&substrate::storage().elections().election_rounds())
+        let election_rounds = runtime
+            .fetch(
+                block_hash,
+                &substrate::storage().elections().election_rounds(),
+            )
             .await?
-            .expect("ElectionRounds not found in storage");
+            .ok_or_else(|| {
+                Error::StorageMissing(format!("Elections::ElectionRounds at {:?}", block_hash))
+            })?;
+
+        // Fetch block timestamp
+        let timestamp = runtime
+            .fetch(block_hash, &substrate::storage().timestamp().now())
+            .await?
+            .unwrap_or_default();
+
+        // Fetch total issuance (used by CurrencyToVote)
+        let total_issuance = runtime
+            .fetch(
+                block_hash,
+                &substrate::storage().balances().total_issuance(),
+            )
+            .await?
+            .unwrap_or_default();
 
         // Fetch Members
-        let members = storage
-            .fetch(&substrate::storage().elections().members())
+        let members = runtime
+            .fetch(block_hash, &substrate::storage().elections().members())
             .await?
-            .expect("Members not found in storage");
+            .ok_or_else(|| {
+                Error::StorageMissing(format!("Elections::Members at {:?}", block_hash))
+            })?;
 
         // Fetch RunnersUp
-        let runners_up = storage
-            .fetch(&substrate::storage().elections().runners_up())
+        let runners_up = runtime
+            .fetch(block_hash, &substrate::storage().elections().runners_up())
             .await?
-            .expect("RunnersUp not found in storage");
+            .ok_or_else(|| {
+                Error::StorageMissing(format!("Elections::RunnersUp at {:?}", block_hash))
+            })?;
 
         // Fetch Candidates
-        let mut candidates = Vec::new();
-        if let Some(mut c) = storage
-            .fetch(&substrate::storage().elections().candidates())
+        let candidates = runtime
+            .fetch(block_hash, &substrate::storage().elections().candidates())
             .await?
-        {
-            candidates.append(&mut c);
-        }
-
-        // Fetch Voting
-        let mut voting = Vec::new();
-        let mut voting_iter = storage
-            .iter(substrate::storage().elections().voting_iter())
-            .await?;
-        while let Some(Ok(kv)) = voting_iter.next().await {
-            let voter = kv.value;
-            let who: [u8; 32] = kv.key_bytes[40..].try_into().unwrap();
-            let voter_account = <SubstrateConfig as subxt::Config>::AccountId::from(who);
-            voting.push((voter_account, voter));
-        }
+            .unwrap_or_default();
 
         Ok(OnchainElectionsData {
             block_hash,
             desired_members,
             desired_runners_up,
             election_rounds,
+            total_issuance,
             members,
             runners_up,
             candidates,
-            voting,
+            voting: vec![],
+            block_number,
+            timestamp,
+            voting_integrity: None,
         })
     }
-}
-
-impl OnchainIdentityProvider for OnchainDataProvider<SubstrateConfig> {
-    async fn map_elections_identities(&self, elections: &mut ApiElectionData) -> Result<()> {
-        use substrate::runtime_types::pallet_identity::types::Data;
 
-        // TODO: This would really benefit from cache shared among all workers
+    /// Resolve identities of given accounts at block hash.
+    /// Sub-accounts get identities of their parent accounts.
+    async fn resolve_identities(
+        &self,
+        addresses: &[String],
+        at: Hash,
+    ) -> Result<HashMap<String, ApiIdentity>> {
+        use substrate::identity::storage::types::super_of::SuperOf;
 
-        // ApiAccount's with resolved names
-        let mut resolved: HashMap<String, String> = HashMap::new();
+        // ApiAccount's with resolved identities
+        let mut resolved: HashMap<String, ApiIdentity> = HashMap::new();
 
-        // Resolve candidates
-        for candidate in &elections.final_results {
-            let account = subxt::utils::AccountId32::from_str(&candidate.id.address)?;
-            let storage = substrate::storage().identity().identity_of(&account);
+        // Accounts not found in cache, with their identity and super account storage keys
+        let mut missing: Vec<(&String, AccountId, Vec<u8>, Vec<u8>)> = Vec::new();
+        for address in addresses {
+            let account = AccountId::from_str(address)?;
+            let cached = self
+                .latest_identities
+                .get(at, &account)
+                .or_else(|| self.identities.get(&(at, account.clone())));
+            match cached {
+                Some(Some(identity)) => {
+                    resolved.insert(address.clone(), identity);
+                }
+                Some(None) => {}
+                None => {
+                    let identity_of = substrate::storage().identity().identity_of(&account);
+                    let super_of = substrate::storage().identity().super_of(&account);
+                    let identity_key = self.api.storage().address_bytes(&identity_of)?;
+                    let super_key = self.api.storage().address_bytes(&super_of)?;
+                    missing.push((address, account, identity_key, super_key));
+                }
+            }
+        }
+        if missing.is_empty() {
+            return Ok(resolved);
+        }
+        let runtime = self.runtime_at(at).await?;
 
-            let resp = self
-                .api
-                .storage()
-                .at_latest()
-                .await?
-                .fetch(&storage)
-                .await?;
+        let keys: Vec<Vec<u8>> = missing
+            .iter()
+            .flat_map(|(_, _, identity_key, super_key)| [identity_key.clone(), super_key.clone()])
+            .collect();
+        let mut retries = 0;
+        let values = self
+            .fetch_storage_values(&keys, Some(at), &mut retries)
+            .await?;
 
-            if let Some(identity) = resp {
-                let display_name = match identity.info.display {
-                    Data::Raw1(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw2(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw3(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw4(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw5(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw6(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw7(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw8(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw9(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw10(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw11(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw12(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw13(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw14(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw15(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw16(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw17(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw18(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw19(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw20(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw21(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw22(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw23(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw24(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw25(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw26(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw27(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw28(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw29(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw30(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw31(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    Data::Raw32(raw_vec) => String::from_utf8(raw_vec.to_vec())?,
-                    _ => {
+        // Fetched identities, sub-accounts are resolved in second pass
+        let mut fetched: Vec<(&String, AccountId, Option<ApiIdentity>)> = Vec::new();
+        let mut subs = Vec::new();
+        for (address, account, identity_key, super_key) in missing {
+            if let Some(value) = values.get(&identity_key) {
+                fetched.push((
+                    address,
+                    account,
+                    decode_registration(&runtime, address, value),
+                ));
+            } else if let Some(value) = values.get(&super_key) {
+                match runtime.decode_value::<SuperOf>("Identity", "SuperOf", value) {
+                    Ok((parent, sub_name)) => subs.push((address, account, parent, sub_name)),
+                    Err(e) => {
                         event!(
                             Level::WARN,
-                            "display name not recognized for address {}: {:?}",
-                            candidate.id.address,
-                            identity.info.display
+                            "super account not decodable for address {}: {}",
+                            address,
+                            e
                         );
-                        continue;
+                        fetched.push((address, account, None));
                     }
-                };
+                }
+            } else {
+                fetched.push((address, account, None));
+            }
+        }
+
+        // Resolve parent identities of sub-accounts
+        let parent_keys = subs
+            .iter()
+            .map(|(_, _, parent, _)| {
+                let identity_of = substrate::storage().identity().identity_of(parent);
+                self.api.storage().address_bytes(&identity_of)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let parent_values = self
+            .fetch_storage_values(&parent_keys, Some(at), &mut retries)
+            .await?;
+        for ((address, account, parent, sub_name), key) in subs.into_iter().zip(&parent_keys) {
+            let parent_identity = parent_values
+                .get(key)
+                .and_then(|value| decode_registration(&runtime, &ss58_address(&parent), value))
+                .unwrap_or_default();
+            let identity = ApiIdentity {
+                parent: Some(ApiParentAccount {
+                    address: ss58_address(&parent),
+                    sub_name: decode_identity_data(&sub_name),
+                }),
+                ..parent_identity
+            };
+            fetched.push((address, account, Some(identity)));
+        }
 
-                resolved.insert(candidate.id.address.clone(), display_name);
+        for (address, account, identity) in fetched {
+            self.latest_identities
+                .insert(at, account.clone(), identity.clone());
+            self.identities.insert((at, account), identity.clone());
+            if let Some(identity) = identity {
+                resolved.insert(address.clone(), identity);
             }
         }
 
-        // Map candidate names in final_results
-        for candidate in elections.final_results.iter_mut() {
-            if let Some(display_name) = resolved.get(&candidate.id.address) {
-                candidate.id.display_name = Some(display_name.clone());
+        Ok(resolved)
+    }
+
+    /// Fetch all voters, enumerating `Voting` keys page by page
+    async fn fetch_voting(
+        &self,
+        runtime: &RuntimeClient,
+        block_hash: Hash,
+    ) -> Result<(Vec<(AccountId, Voter<AccountId, u128>)>, VotingIntegrity)> {
+        let (keys, mut integrity) = self.voting_keys(block_hash).await?;
+
+        // Fetch and decode values
+        let mut values = self
+            .fetch_storage_values(&keys, Some(block_hash), &mut integrity.retries)
+            .await?;
+        let mut voting = Vec::with_capacity(keys.len());
+        for key in &keys {
+            let value = values.remove(key).ok_or_else(|| {
+                Error::StorageMissing(format!(
+                    "Elections::Voting value for key 0x{}",
+                    hex::encode(key)
+                ))
+            })?;
+            let voter: Voter<AccountId, u128> =
+                runtime.decode_value("Elections", "Voting", &value)?;
+            // Key is twox128(pallet) ++ twox128(storage) ++ twox64(account) ++ account
+            let who: [u8; 32] = key
+                .get(40..)
+                .and_then(|account| account.try_into().ok())
+                .ok_or_else(|| {
+                    Error::Decode(format!(
+                        "Invalid Elections::Voting key: 0x{}",
+                        hex::encode(key)
+                    ))
+                })?;
+            voting.push((AccountId::from(who), voter));
+        }
+
+        event!(
+            Level::DEBUG,
+            "Fetched {} voters in {} pages ({} retries)",
+            voting.len(),
+            integrity.pages,
+            integrity.retries
+        );
+
+        Ok((voting, integrity))
+    }
+
+    /// Enumerate `Voting` keys page by page, counting them as they are received.
+    /// Failed requests are retried, resuming from the last enumerated key.
+    async fn voting_keys(&self, block_hash: Hash) -> Result<(Vec<Vec<u8>>, VotingIntegrity)> {
+        let prefix = self.api.storage().address_bytes(&subxt::dynamic::storage(
+            "Elections",
+            "Voting",
+            (),
+        ))?;
+        let mut integrity = VotingIntegrity::default();
+
+        let mut keys: Vec<Vec<u8>> = Vec::new();
+        loop {
+            let start_key = keys.last().map(|key| key.as_slice());
+            let page = retry_request("state_getKeysPaged", &mut integrity.retries, || {
+                self.rpc.state_get_keys_paged(
+                    &prefix,
+                    STORAGE_PAGE_SIZE,
+                    start_key,
+                    Some(block_hash),
+                )
+            })
+            .await?;
+            let last_page = page.len() < STORAGE_PAGE_SIZE as usize;
+            integrity.pages += 1;
+            integrity.keys += page.len() as u32;
+            keys.extend(page);
+            if last_page {
+                break;
+            }
+        }
+
+        Ok((keys, integrity))
+    }
+
+    /// Query storage values in bulk instead of one request per key (keys without value are omitted).
+    /// Failed requests are retried and counted in `retries`.
+    async fn fetch_storage_values(
+        &self,
+        keys: &[Vec<u8>],
+        at: Option<Hash>,
+        retries: &mut u32,
+    ) -> Result<HashMap<Vec<u8>, Vec<u8>>> {
+        let mut values = HashMap::new();
+        for chunk in keys.chunks(STORAGE_QUERY_CHUNK_SIZE) {
+            let change_sets = retry_request("state_queryStorageAt", retries, || {
+                self.rpc
+                    .state_query_storage_at(chunk.iter().map(|key| key.as_slice()), at)
+            })
+            .await?;
+            values.extend(
+                change_sets
+                    .into_iter()
+                    .flat_map(|change_set| change_set.changes)
+                    .filter_map(|(key, value)| Some((key.0, value?.0))),
+            );
+        }
+
+        Ok(values)
+    }
+}
+
+impl OnchainIdentityProvider for OnchainDataProvider<SubstrateConfig> {
+    async fn map_elections_identities(
+        &self,
+        elections: &mut ApiElectionData,
+        at: Hash,
+        include_current: bool,
+    ) -> Result<()> {
+        // Resolve candidates and voters
+        let addresses: Vec<String> = elections
+            .final_results
+            .iter()
+            .map(|candidate| candidate.id.address.clone())
+            .chain(
+                elections
+                    .voters
+                    .iter()
+                    .map(|voter| voter.id.address.clone()),
+            )
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let resolved = self.resolve_identities(&addresses, at).await?;
+        for account in elections.accounts_mut() {
+            if let Some(identity) = resolved.get(&account.address) {
+                account.display_name = identity.display_name();
+                account.identity = Some(identity.clone());
             }
         }
 
-        // Map candidate names in candidates
-        for candidate in elections.candidates.iter_mut() {
-            if let Some(display_name) = resolved.get(&candidate.id.address) {
-                candidate.id.display_name = Some(display_name.clone());
+        // Names may have changed since the queried block
+        if include_current {
+            // Latest followed block is preferred, as its identities are mostly cached
+            let current_hash = match self.latest_identities.block_hash() {
+                Some(hash) => hash,
+                None => self.latest_blockhash(LatestBlock::Finalized).await?,
+            };
+            let current = self.resolve_identities(&addresses, current_hash).await?;
+            for account in elections.accounts_mut() {
+                if let Some(identity) = current.get(&account.address) {
+                    account.current_display_name = identity.display_name();
+                }
             }
         }
 
-        // Map candidates in voters
-        for voter in elections.voters.iter_mut() {
-            for candidate in &mut voter.votes {
-                if let Some(display_name) = resolved.get(&candidate.address) {
-                    candidate.display_name = Some(display_name.clone());
+        Ok(())
+    }
+}
+
+/// Decode on-chain identity registration (None if not decodable)
+fn decode_registration(
+    runtime: &RuntimeClient,
+    address: &str,
+    value: &[u8],
+) -> Option<ApiIdentity> {
+    use substrate::identity::storage::types::identity_of::IdentityOf;
+
+    runtime
+        .decode_value::<IdentityOf>("Identity", "IdentityOf", value)
+        .inspect_err(|e| {
+            event!(
+                Level::WARN,
+                "identity not decodable for address {}: {}",
+                address,
+                e
+            )
+        })
+        .ok()
+        .map(decode_identity)
+}
+
+/// Convert on-chain identity registration to API identity
+fn decode_identity(
+    identity: substrate::identity::storage::types::identity_of::IdentityOf,
+) -> ApiIdentity {
+    use substrate::runtime_types::pallet_identity::types::Judgement;
+
+    let judgements = identity
+        .judgements
+        .0
+        .iter()
+        .map(|(registrar, judgement)| ApiJudgement {
+            registrar: *registrar,
+            judgement: match judgement {
+                Judgement::Unknown => "unknown",
+                Judgement::FeePaid(_) => "feePaid",
+                Judgement::Reasonable => "reasonable",
+                Judgement::KnownGood => "knownGood",
+                Judgement::OutOfDate => "outOfDate",
+                Judgement::LowQuality => "lowQuality",
+                Judgement::Erroneous => "erroneous",
+            }
+            .to_string(),
+        })
+        .collect();
+
+    ApiIdentity {
+        display: decode_identity_data(&identity.info.display),
+        legal: decode_identity_data(&identity.info.legal),
+        web: decode_identity_data(&identity.info.web),
+        email: decode_identity_data(&identity.info.email),
+        twitter: decode_identity_data(&identity.info.twitter),
+        judgements,
+        parent: None,
+    }
+}
+
+/// Decode identity field (None if not set)
+fn decode_identity_data(
+    data: &substrate::runtime_types::pallet_identity::types::Data,
+) -> Option<ApiIdentityData> {
+    use substrate::runtime_types::pallet_identity::types::Data;
+
+    match data {
+        Data::None | Data::Raw0(_) => None,
+        Data::Raw1(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw2(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw3(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw4(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw5(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw6(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw7(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw8(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw9(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw10(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw11(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw12(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw13(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw14(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw15(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw16(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw17(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw18(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw19(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw20(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw21(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw22(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw23(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw24(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw25(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw26(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw27(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw28(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw29(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw30(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw31(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::Raw32(raw) => Some(ApiIdentityData::Raw(
+            String::from_utf8_lossy(raw).into_owned(),
+        )),
+        Data::BlakeTwo256(hash) => Some(ApiIdentityData::BlakeTwo256(hex_hash(hash))),
+        Data::Sha256(hash) => Some(ApiIdentityData::Sha256(hex_hash(hash))),
+        Data::Keccak256(hash) => Some(ApiIdentityData::Keccak256(hex_hash(hash))),
+        Data::ShaThree256(hash) => Some(ApiIdentityData::ShaThree256(hex_hash(hash))),
+    }
+}
+
+/// Collect accounts whose votes a call dispatched with `origin` (None if unsigned) may
+/// change. Returns false if the call can't be classified, i.e. it may dispatch calls
+/// with other origins (derivative accounts, multisigs, sudo, governance, contracts)
+/// or change votes in other ways.
+///
+/// Batches and proxy calls are classified by the calls they dispatch.
+fn call_voting_changes<T>(
+    pallet: &str,
+    call: &str,
+    fields: &Composite<T>,
+    origin: Option<&AccountId>,
+    accounts: &mut Vec<AccountId>,
+) -> bool {
+    match (pallet, call) {
+        ("Elections", _) => match origin {
+            Some(origin) => accounts.push(origin.clone()),
+            None => return false,
+        },
+        ("Utility", "batch" | "batch_all" | "force_batch") => {
+            let Some(calls) = call_field(fields, "calls") else {
+                return false;
+            };
+            let ValueDef::Composite(calls) = &calls.value else {
+                return false;
+            };
+            for call in calls.values() {
+                if !nested_call_voting_changes(call, origin, accounts) {
+                    return false;
                 }
             }
         }
+        ("Utility", "with_weight") => {
+            let Some(call) = call_field(fields, "call") else {
+                return false;
+            };
+            return nested_call_voting_changes(call, origin, accounts);
+        }
+        ("Proxy", "proxy" | "proxy_announced") => {
+            let real = call_field(fields, "real").and_then(call_account);
+            let (Some(real), Some(call)) = (real, call_field(fields, "call")) else {
+                return false;
+            };
+            return nested_call_voting_changes(call, Some(&real), accounts);
+        }
+        (pallet, _) if VOTING_NEUTRAL_PALLETS.contains(&pallet) => {}
+        _ => return false,
+    }
+
+    true
+}
 
-        // Map candidate names in rounds.scores
-        for round in elections.rounds.iter_mut() {
-            for candidate in round.scores.iter_mut() {
-                if let Some(display_name) = resolved.get(&candidate.id.address) {
-                    candidate.id.display_name = Some(display_name.clone());
+/// Classify call passed as argument of another call (see `call_voting_changes`)
+fn nested_call_voting_changes<T>(
+    call: &Value<T>,
+    origin: Option<&AccountId>,
+    accounts: &mut Vec<AccountId>,
+) -> bool {
+    // Runtime calls are variants of pallets, whose values are variants of calls
+    let ValueDef::Variant(pallet) = &call.value else {
+        return false;
+    };
+    let Some(Value {
+        value: ValueDef::Variant(call),
+        ..
+    }) = pallet.values.values().next()
+    else {
+        return false;
+    };
+
+    call_voting_changes(&pallet.name, &call.name, &call.values, origin, accounts)
+}
+
+/// Named argument of a call
+fn call_field<'a, T>(fields: &'a Composite<T>, name: &str) -> Option<&'a Value<T>> {
+    match fields {
+        Composite::Named(fields) => fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value),
+        Composite::Unnamed(_) => None,
+    }
+}
+
+/// Account passed as call argument (`MultiAddress::Id` or plain account)
+fn call_account<T>(value: &Value<T>) -> Option<AccountId> {
+    fn collect_bytes<T>(value: &Value<T>, bytes: &mut Vec<u8>) -> Option<()> {
+        match &value.value {
+            ValueDef::Composite(composite) => {
+                for value in composite.values() {
+                    collect_bytes(value, bytes)?;
                 }
             }
+            ValueDef::Primitive(Primitive::U128(byte)) => bytes.push(u8::try_from(*byte).ok()?),
+            _ => return None,
         }
+        Some(())
+    }
 
-        Ok(())
+    let account = match &value.value {
+        ValueDef::Variant(variant) if variant.name == "Id" => variant.values.values().next()?,
+        ValueDef::Composite(_) => value,
+        _ => return None,
+    };
+    let mut bytes = Vec::with_capacity(32);
+    collect_bytes(account, &mut bytes)?;
+    let bytes: [u8; 32] = bytes.try_into().ok()?;
+
+    Some(AccountId::from(bytes))
+}
+
+/// Format hash as 0x-prefixed hex string
+fn hex_hash(hash: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+/// Run RPC request, retrying on transport errors and timeouts (counted in `retries`).
+/// Errors returned by the node are deterministic, so they are passed through.
+async fn retry_request<T, F, Fut>(name: &str, retries: &mut u32, mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = std::result::Result<T, RpcError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Ok(value) => return Ok(value),
+            Err(e) if !is_connection_error(&e) => return Err(Error::from(e).into()),
+            Err(e) if attempt < STORAGE_RETRIES => {
+                attempt += 1;
+                *retries += 1;
+                event!(
+                    Level::WARN,
+                    "{} failed, retrying ({}/{}): {}",
+                    name,
+                    attempt,
+                    STORAGE_RETRIES,
+                    e
+                );
+                actix_web::rt::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
+            }
+            Err(e) => return Err(Error::NodeUnreachable(format!("{}: {}", name, e)).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: u8) -> AccountId {
+        AccountId::from([id; 32])
+    }
+
+    /// `MultiAddress::Id` call argument
+    fn address(id: u8) -> Value {
+        Value::unnamed_variant(
+            "Id",
+            [Value::unnamed_composite([Value::from_bytes([id; 32])])],
+        )
+    }
+
+    /// Runtime call argument
+    fn call(pallet: &str, call: &str, fields: Vec<(&str, Value)>) -> Value {
+        Value::unnamed_variant(pallet, [Value::named_variant(call, fields)])
+    }
+
+    fn vote() -> Value {
+        call(
+            "Elections",
+            "vote",
+            vec![
+                ("votes", Value::unnamed_composite([])),
+                ("value", Value::u128(1)),
+            ],
+        )
+    }
+
+    fn batch(calls: Vec<Value>) -> Value {
+        call(
+            "Utility",
+            "batch_all",
+            vec![("calls", Value::unnamed_composite(calls))],
+        )
+    }
+
+    /// Accounts whose votes a call signed by `signer` may change (None if unclassified)
+    fn voting_changes(call: &Value, signer: u8) -> Option<Vec<AccountId>> {
+        let mut accounts = vec![];
+        nested_call_voting_changes(call, Some(&account(signer)), &mut accounts).then_some(accounts)
+    }
+
+    #[test]
+    fn batched_proxy_call_changes_votes_of_proxied_account() {
+        let proxy = call(
+            "Proxy",
+            "proxy",
+            vec![
+                ("real", address(2)),
+                ("force_proxy_type", Value::unnamed_variant("None", [])),
+                ("call", vote()),
+            ],
+        );
+        let transfer = call(
+            "Balances",
+            "transfer_keep_alive",
+            vec![("dest", address(3)), ("value", Value::u128(1))],
+        );
+
+        assert_eq!(
+            voting_changes(&batch(vec![proxy, transfer]), 1),
+            Some(vec![account(2)])
+        );
+    }
+
+    #[test]
+    fn batched_elections_call_changes_votes_of_signer() {
+        assert_eq!(
+            voting_changes(&batch(vec![vote()]), 1),
+            Some(vec![account(1)])
+        );
+    }
+
+    #[test]
+    fn calls_dispatched_with_other_origins_are_unclassified() {
+        let as_derivative = call(
+            "Utility",
+            "as_derivative",
+            vec![("index", Value::u128(0)), ("call", vote())],
+        );
+        let as_multi = call(
+            "Multisig",
+            "as_multi",
+            vec![
+                ("other_signatories", Value::unnamed_composite([])),
+                ("call", vote()),
+            ],
+        );
+        let close = call("TechnicalCommittee", "close", vec![]);
+
+        for call in [as_derivative, as_multi, close] {
+            assert_eq!(voting_changes(&batch(vec![call]), 1), None);
+        }
+    }
+
+    #[test]
+    fn latest_identities_invalidated_by_identity_changes() {
+        let identities = LatestIdentities::new(Duration::from_secs(60));
+        let hash = |n: u8| Hash::from([n; 32]);
+        let parent = ApiIdentity::default();
+        let sub = ApiIdentity {
+            parent: Some(ApiParentAccount {
+                address: ss58_address(&account(1)),
+                sub_name: None,
+            }),
+            ..Default::default()
+        };
+
+        // Nothing is cached before the first block is followed
+        identities.insert(hash(1), account(1), Some(parent.clone()));
+        assert!(identities.get(hash(1), &account(1)).is_none());
+
+        identities.advance(1, hash(1), &[]);
+        for (id, identity) in [(1, Some(parent)), (2, Some(sub)), (3, None)] {
+            identities.insert(hash(1), account(id), identity);
+        }
+        assert!(identities.get(hash(1), &account(1)).is_some());
+        assert!(identities.get(hash(2), &account(1)).is_none());
+
+        // Unchanged identities carry over, sub-accounts follow their parent
+        identities.advance(2, hash(2), &[account(1)]);
+        assert!(identities.get(hash(2), &account(1)).is_none());
+        assert!(identities.get(hash(2), &account(2)).is_none());
+        assert!(matches!(identities.get(hash(2), &account(3)), Some(None)));
+
+        // Skipped blocks may have changed any identity
+        identities.advance(4, hash(4), &[]);
+        assert!(identities.get(hash(4), &account(3)).is_none());
     }
 }
@@ -1,14 +1,165 @@
 use super::*;
-use actix_web::Result;
+use std::collections::BTreeMap;
 
 pub fn simulate_weighted_phragmen_elections(
     onchain_data: &OnchainElectionsData,
-) -> Result<PhragmenOutputs> {
+    params: &SimulationParams,
+) -> Result<PhragmenOutputs, Error> {
+    // Never simulate on a partial electorate
+    match onchain_data.voting_integrity {
+        Some(integrity) if onchain_data.voting.len() != integrity.keys as usize => {
+            return Err(Error::IncompleteData(format!(
+                "{} voters, {} Elections::Voting keys",
+                onchain_data.voting.len(),
+                integrity.keys
+            )));
+        }
+        Some(_) => {}
+        None => event!(
+            Level::WARN,
+            "No voting integrity record at block hash {:?}, voter set not verified",
+            onchain_data.block_hash
+        ),
+    }
+
     // Convert on-chain data to Phragmen inputs
-    let phragmen_inputs = prepare_phragmen_inputs(&onchain_data);
+    let phragmen_inputs = prepare_phragmen_inputs(onchain_data);
+
+    // Balancing tolerance is compared with vote weights
+    let currency_to_vote = phragmen_inputs.currency_to_vote;
+    let balancing = params.balancing.map(|config| BalancingConfig {
+        tolerance: currency_to_vote.to_vote(config.tolerance) as ExtendedBalance,
+        ..config
+    });
+
+    // Run selected algorithm
+    match params.algorithm {
+        Algorithm::SeqPhragmen => run_phragmen(phragmen_inputs, balancing),
+        Algorithm::Phragmms => run_phragmms(phragmen_inputs, balancing),
+    }
+}
+
+pub fn check_pjr(
+    onchain_data: &OnchainElectionsData,
+    params: &SimulationParams,
+) -> Result<PjrOutputs, Error> {
+    use sp_npos_elections::{standard_threshold, t_pjr_check, t_pjr_counter_example, to_supports};
+
+    // Simulate elections
+    let phragmen = simulate_weighted_phragmen_elections(onchain_data, params)?;
+    let PhragmenInputs {
+        candidates, voters, ..
+    } = prepare_phragmen_inputs(onchain_data);
+
+    // Build supports from simulated assignments
+    let supports = to_supports(&phragmen.staked_assignments);
+
+    // Check PJR with standard threshold
+    let threshold = standard_threshold(
+        supports.len(),
+        voters.iter().map(|(_, stake, _)| *stake as ExtendedBalance),
+    );
+    let (satisfied, counter_example) =
+        match t_pjr_check(&supports, candidates.clone(), voters.clone(), threshold) {
+            Ok(()) => (true, None),
+            Err(counter_example) => (
+                false,
+                t_pjr_counter_example(counter_example, &supports, candidates, voters, threshold),
+            ),
+        };
 
-    // Run Phragmen
-    run_phragmen(phragmen_inputs)
+    Ok(PjrOutputs {
+        satisfied,
+        threshold,
+        counter_example,
+        currency_to_vote: phragmen.currency_to_vote,
+        algorithm: phragmen.algorithm,
+    })
+}
+
+/// Simulate election held at `onchain_data` block on data from its parent block,
+/// and compare the results with seats stored on-chain
+pub fn reconcile_election(
+    parent_data: &OnchainElectionsData,
+    onchain_data: &OnchainElectionsData,
+    params: &SimulationParams,
+) -> Result<ReconciliationOutputs, Error> {
+    if onchain_data.election_rounds != parent_data.election_rounds + 1 {
+        return Err(Error::BadRequest(format!(
+            "No election held at block hash {:?}",
+            onchain_data.block_hash
+        )));
+    }
+
+    let phragmen = simulate_weighted_phragmen_elections(parent_data, params)?;
+
+    // Winners are split into members and runners-up the same way as in `pallet_elections_phragmen`
+    let mut winners: Vec<(AccountId, u128)> = phragmen
+        .result
+        .winners
+        .iter()
+        .filter(|(_, stake)| *stake > 0)
+        .map(|(who, stake)| (who.clone(), phragmen.currency_to_vote.to_currency(*stake)))
+        .collect();
+    let split_point = (parent_data.desired_members as usize).min(winners.len());
+    let mut simulated_members: Vec<(AccountId, u128)> = winners.drain(..split_point).collect();
+    simulated_members.sort_by(|a, b| a.0.cmp(&b.0));
+    let simulated_runners_up = winners;
+
+    let actual_members: Vec<(AccountId, u128)> = onchain_data
+        .members
+        .iter()
+        .map(|seat_holder| (seat_holder.who.clone(), seat_holder.stake))
+        .collect();
+    // Runners-up are stored on-chain from the least to the most backed
+    let actual_runners_up: Vec<(AccountId, u128)> = onchain_data
+        .runners_up
+        .iter()
+        .rev()
+        .map(|seat_holder| (seat_holder.who.clone(), seat_holder.stake))
+        .collect();
+
+    Ok(ReconciliationOutputs {
+        block_hash: onchain_data.block_hash,
+        parent_block_hash: parent_data.block_hash,
+        algorithm: phragmen.algorithm,
+        members: compare_seats(&simulated_members, &actual_members),
+        runners_up: compare_seats(&simulated_runners_up, &actual_runners_up),
+    })
+}
+
+/// Pair simulated and on-chain seats by account (simulated ones first)
+fn compare_seats(
+    simulated: &[(AccountId, u128)],
+    actual: &[(AccountId, u128)],
+) -> Vec<SeatComparison> {
+    let stake_of = |seats: &[(AccountId, u128)], who: &AccountId| {
+        seats
+            .iter()
+            .find(|(account, _)| account == who)
+            .map(|(_, stake)| *stake)
+    };
+
+    let mut seats: Vec<SeatComparison> = simulated
+        .iter()
+        .map(|(who, stake)| SeatComparison {
+            who: who.clone(),
+            simulated_stake: Some(*stake),
+            actual_stake: stake_of(actual, who),
+        })
+        .collect();
+    seats.extend(
+        actual
+            .iter()
+            .filter(|(who, _)| stake_of(simulated, who).is_none())
+            .map(|(who, stake)| SeatComparison {
+                who: who.clone(),
+                simulated_stake: None,
+                actual_stake: Some(*stake),
+            }),
+    );
+
+    seats
 }
 
 pub fn prepare_phragmen_inputs(onchain: &OnchainElectionsData) -> PhragmenInputs {
@@ -39,33 +190,341 @@ pub fn prepare_phragmen_inputs(onchain: &OnchainElectionsData) -> PhragmenInputs
         .cloned()
         .collect::<Vec<_>>();
 
+    // Convert stakes to vote weights the same way the runtime does
+    let currency_to_vote = CurrencyToVote::new(onchain.total_issuance);
+
     // Collect Voters
     let voters = onchain
         .voting
         .iter()
         .cloned()
-        .map(|(voter, Voter { stake, votes, .. })| (voter, stake as u64, votes))
+        .map(|(voter, Voter { stake, votes, .. })| (voter, currency_to_vote.to_vote(stake), votes))
         .collect::<Vec<_>>();
 
     PhragmenInputs {
         to_elect,
         candidates: candidate_ids,
         voters,
+        currency_to_vote,
     }
 }
 
-pub fn run_phragmen(inputs: PhragmenInputs) -> Result<PhragmenOutputs> {
+pub fn run_phragmen(
+    inputs: PhragmenInputs,
+    balancing: Option<BalancingConfig>,
+) -> Result<PhragmenOutputs, Error> {
+    let stakes = voter_stakes(&inputs);
     match sp_npos_elections::seq_phragmen::<AccountId, Perbill>(
         inputs.to_elect,
         inputs.candidates,
         inputs.voters,
-        None,
+        balancing,
+    ) {
+        Ok((result, candidates, traces)) => Ok(PhragmenOutputs {
+            staked_assignments: stake_assignments(&result, &stakes)?,
+            result,
+            candidates,
+            traces,
+            currency_to_vote: inputs.currency_to_vote,
+            algorithm: Algorithm::SeqPhragmen,
+            balancing,
+        }),
+        Err(e) => Err(Error::Phragmen(format!("{:?}", e))),
+    }
+}
+
+pub fn run_phragmms(
+    inputs: PhragmenInputs,
+    balancing: Option<BalancingConfig>,
+) -> Result<PhragmenOutputs, Error> {
+    let stakes = voter_stakes(&inputs);
+    match sp_npos_elections::phragmms_with_tracing::<AccountId, Perbill>(
+        inputs.to_elect,
+        inputs.candidates,
+        inputs.voters,
+        balancing,
     ) {
         Ok((result, candidates, traces)) => Ok(PhragmenOutputs {
+            staked_assignments: stake_assignments(&result, &stakes)?,
             result,
             candidates,
             traces,
+            currency_to_vote: inputs.currency_to_vote,
+            algorithm: Algorithm::Phragmms,
+            balancing,
         }),
-        Err(_) => Err(error::ErrorBadRequest("Phragmen internal error")),
+        Err(e) => Err(Error::Phragmen(format!("{:?}", e))),
+    }
+}
+
+/// Vote weight of every voter
+fn voter_stakes(inputs: &PhragmenInputs) -> BTreeMap<AccountId, VoteWeight> {
+    inputs
+        .voters
+        .iter()
+        .map(|(who, stake, _)| (who.clone(), *stake))
+        .collect()
+}
+
+/// Convert voters' ratio assignments into stake assignments
+fn stake_assignments(
+    result: &ElectionResult<AccountId, Perbill>,
+    stakes: &BTreeMap<AccountId, VoteWeight>,
+) -> Result<Vec<StakedAssignment<AccountId>>, Error> {
+    sp_npos_elections::assignment_ratio_to_staked_normalized(
+        result.assignments.clone(),
+        |who: &AccountId| stakes.get(who).copied().unwrap_or_default(),
+    )
+    .map_err(|e| Error::Phragmen(format!("{:?}", e)))
+}
+
+/// Emulation of the runtime's `U128CurrencyToVote` conversion.
+///
+/// Balances are scaled down by `total_issuance / VoteWeight::MAX` (at least 1), so that the sum of
+/// all stakes always fits in `VoteWeight`.
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+pub struct CurrencyToVote {
+    pub total_issuance: u128,
+}
+
+impl CurrencyToVote {
+    pub fn new(total_issuance: u128) -> Self {
+        Self { total_issuance }
+    }
+
+    /// Factor by which balances are divided when converted to vote weights
+    pub fn factor(&self) -> u128 {
+        (self.total_issuance / VoteWeight::MAX as u128).max(1)
+    }
+
+    /// Convert balance to vote weight
+    pub fn to_vote(&self, value: u128) -> VoteWeight {
+        (value / self.factor())
+            .try_into()
+            .unwrap_or(VoteWeight::MAX)
+    }
+
+    /// Convert vote weight (or any value computed from vote weights) back to balance
+    pub fn to_currency(&self, value: ExtendedBalance) -> u128 {
+        value.saturating_mul(self.factor())
+    }
+
+    /// Balance lost when converting `value` to vote weight
+    pub fn precision_loss(&self, value: u128) -> u128 {
+        value.saturating_sub(self.to_currency(self.to_vote(value) as ExtendedBalance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: u8) -> AccountId {
+        AccountId::from([id; 32])
+    }
+
+    fn seat_holder(id: u8, stake: u128) -> SeatHolder<AccountId, u128> {
+        SeatHolder {
+            who: account(id),
+            stake,
+            deposit: 1,
+        }
+    }
+
+    /// Elections data before an election of one member and one runner-up, in which
+    /// candidate 1 is backed by 100, 2 by 50 and 3 by 10
+    fn parent_data() -> OnchainElectionsData {
+        let voting: Vec<_> = [(10, 100, 1), (20, 50, 2), (30, 10, 3)]
+            .into_iter()
+            .map(|(voter, stake, candidate)| {
+                let voter_data = Voter {
+                    votes: vec![account(candidate)],
+                    stake,
+                    deposit: 1,
+                };
+                (account(voter), voter_data)
+            })
+            .collect();
+
+        OnchainElectionsData {
+            desired_members: 1,
+            desired_runners_up: 1,
+            total_issuance: 1_000,
+            candidates: vec![(account(1), 1), (account(2), 1), (account(3), 1)],
+            voting_integrity: Some(VotingIntegrity {
+                keys: voting.len() as u32,
+                ..Default::default()
+            }),
+            voting,
+            ..Default::default()
+        }
+    }
+
+    /// Elections data after the election, with given seats
+    fn elected_data(
+        members: Vec<SeatHolder<AccountId, u128>>,
+        runners_up: Vec<SeatHolder<AccountId, u128>>,
+    ) -> OnchainElectionsData {
+        OnchainElectionsData {
+            election_rounds: 1,
+            members,
+            runners_up,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reconcile_election_with_matching_seats() {
+        let onchain_data = elected_data(vec![seat_holder(1, 100)], vec![seat_holder(2, 50)]);
+
+        let reconciliation =
+            reconcile_election(&parent_data(), &onchain_data, &SimulationParams::default())
+                .unwrap();
+
+        assert!(reconciliation.matches());
+        assert_eq!(reconciliation.members.len(), 1);
+        assert_eq!(reconciliation.members[0].who, account(1));
+        assert_eq!(reconciliation.members[0].simulated_stake, Some(100));
+        assert_eq!(reconciliation.runners_up.len(), 1);
+        assert_eq!(reconciliation.runners_up[0].who, account(2));
+    }
+
+    #[test]
+    fn reconcile_election_with_mismatching_seats() {
+        let onchain_data = elected_data(vec![seat_holder(3, 10)], vec![seat_holder(2, 50)]);
+
+        let reconciliation =
+            reconcile_election(&parent_data(), &onchain_data, &SimulationParams::default())
+                .unwrap();
+
+        assert!(!reconciliation.matches());
+        let members: Vec<_> = reconciliation
+            .members
+            .iter()
+            .map(|seat| (seat.who.clone(), seat.simulated_stake, seat.actual_stake))
+            .collect();
+        assert_eq!(
+            members,
+            vec![(account(1), Some(100), None), (account(3), None, Some(10))]
+        );
+        assert!(
+            reconciliation
+                .runners_up
+                .iter()
+                .all(SeatComparison::matches)
+        );
+    }
+
+    #[test]
+    fn reconcile_election_requires_election_at_block() {
+        let mut onchain_data = elected_data(vec![seat_holder(1, 100)], vec![seat_holder(2, 50)]);
+        onchain_data.election_rounds = 0;
+
+        let result =
+            reconcile_election(&parent_data(), &onchain_data, &SimulationParams::default());
+
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+    }
+
+    #[test]
+    fn simulation_rejects_partial_electorate() {
+        let mut onchain_data = parent_data();
+        onchain_data.voting.pop();
+
+        let result =
+            simulate_weighted_phragmen_elections(&onchain_data, &SimulationParams::default());
+
+        assert!(matches!(result, Err(Error::IncompleteData(_))));
+    }
+
+    #[test]
+    fn compare_seats_pairs_seats_by_account() {
+        let simulated = vec![(account(1), 100), (account(2), 50)];
+        let actual = vec![(account(2), 40), (account(3), 30)];
+
+        let seats = compare_seats(&simulated, &actual);
+
+        let seats: Vec<_> = seats
+            .iter()
+            .map(|seat| {
+                (
+                    seat.who.clone(),
+                    seat.simulated_stake,
+                    seat.actual_stake,
+                    seat.matches(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            seats,
+            vec![
+                (account(1), Some(100), None, false),
+                (account(2), Some(50), Some(40), false),
+                (account(3), None, Some(30), false),
+            ]
+        );
+        assert!(
+            compare_seats(&simulated, &simulated)
+                .iter()
+                .all(SeatComparison::matches)
+        );
+    }
+
+    #[test]
+    fn balancing_tolerance_is_converted_from_balance() {
+        let mut onchain_data = parent_data();
+        onchain_data.total_issuance = VoteWeight::MAX as u128 * 1000;
+        for (_, voter) in onchain_data.voting.iter_mut() {
+            voter.stake *= 1000;
+        }
+        let params = SimulationParams {
+            balancing: Some(BalancingConfig {
+                iterations: 10,
+                tolerance: 5_500,
+            }),
+            ..Default::default()
+        };
+
+        let phragmen = simulate_weighted_phragmen_elections(&onchain_data, &params).unwrap();
+
+        let balancing = phragmen.balancing.unwrap();
+        assert_eq!(balancing.tolerance, 5);
+        assert_eq!(
+            phragmen.currency_to_vote.to_currency(balancing.tolerance),
+            5_000
+        );
+    }
+
+    #[test]
+    fn currency_to_vote_is_lossless_below_vote_weight_max() {
+        let currency_to_vote = CurrencyToVote::new(VoteWeight::MAX as u128);
+
+        assert_eq!(currency_to_vote.factor(), 1);
+        assert_eq!(currency_to_vote.to_vote(1_234_567), 1_234_567);
+        assert_eq!(currency_to_vote.to_currency(1_234_567), 1_234_567);
+        assert_eq!(currency_to_vote.precision_loss(1_234_567), 0);
+        assert_eq!(CurrencyToVote::new(0).factor(), 1);
+    }
+
+    #[test]
+    fn currency_to_vote_scales_down_by_issuance() {
+        let currency_to_vote = CurrencyToVote::new(VoteWeight::MAX as u128 * 1000 + 5);
+
+        assert_eq!(currency_to_vote.factor(), 1000);
+        assert_eq!(currency_to_vote.to_vote(1_234_567), 1_234);
+        assert_eq!(currency_to_vote.to_currency(1_234), 1_234_000);
+        assert_eq!(currency_to_vote.precision_loss(1_234_567), 567);
+        assert_eq!(currency_to_vote.precision_loss(1_234_000), 0);
+    }
+
+    #[test]
+    fn currency_to_vote_saturates() {
+        let currency_to_vote = CurrencyToVote::new(0);
+
+        assert_eq!(currency_to_vote.to_vote(u128::MAX), VoteWeight::MAX);
+        assert_eq!(
+            CurrencyToVote::new(u128::MAX).to_currency(ExtendedBalance::MAX),
+            u128::MAX
+        );
     }
 }
@@ -0,0 +1,140 @@
+use super::*;
+
+/// Source of elections data selected at startup
+#[derive(Clone)]
+pub enum DataProvider {
+    /// Live node
+    Onchain(OnchainDataProvider<SubstrateConfig>),
+    /// Live node, with latest elections storage mirrored locally
+    Mirror(MirrorDataProvider),
+    /// Snapshot files
+    Snapshot(SnapshotDataProvider),
+}
+
+impl OnchainElectionsDataProvider for DataProvider {
+    async fn elections_at_blockhash(&self, hash: Option<Hash>) -> Result<OnchainElectionsData> {
+        match self {
+            DataProvider::Onchain(provider) => provider.elections_at_blockhash(hash).await,
+            DataProvider::Mirror(provider) => provider.elections_at_blockhash(hash).await,
+            DataProvider::Snapshot(provider) => provider.elections_at_blockhash(hash).await,
+        }
+    }
+
+    async fn parent_blockhash(&self, hash: Hash) -> Result<Hash> {
+        match self {
+            DataProvider::Onchain(provider) => provider.parent_blockhash(hash).await,
+            DataProvider::Mirror(provider) => provider.parent_blockhash(hash).await,
+            DataProvider::Snapshot(provider) => provider.parent_blockhash(hash).await,
+        }
+    }
+
+    async fn blockhash_at(&self, number: u32) -> Result<Hash> {
+        match self {
+            DataProvider::Onchain(provider) => provider.blockhash_at(number).await,
+            DataProvider::Mirror(provider) => provider.blockhash_at(number).await,
+            DataProvider::Snapshot(provider) => provider.blockhash_at(number).await,
+        }
+    }
+
+    async fn latest_blockhash(&self, latest: LatestBlock) -> Result<Hash> {
+        match self {
+            DataProvider::Onchain(provider) => provider.latest_blockhash(latest).await,
+            DataProvider::Mirror(provider) => provider.latest_blockhash(latest).await,
+            DataProvider::Snapshot(provider) => provider.latest_blockhash(latest).await,
+        }
+    }
+}
+
+impl OnchainIdentityProvider for DataProvider {
+    async fn map_elections_identities(
+        &self,
+        elections: &mut ApiElectionData,
+        at: Hash,
+        include_current: bool,
+    ) -> Result<()> {
+        match self {
+            DataProvider::Onchain(provider) => {
+                provider
+                    .map_elections_identities(elections, at, include_current)
+                    .await
+            }
+            DataProvider::Mirror(provider) => {
+                provider
+                    .map_elections_identities(elections, at, include_current)
+                    .await
+            }
+            DataProvider::Snapshot(provider) => {
+                provider
+                    .map_elections_identities(elections, at, include_current)
+                    .await
+            }
+        }
+    }
+}
+
+/// Data provider with elections data at block hashes cached
+#[derive(Clone)]
+pub struct CachedDataProvider<P> {
+    provider: P,
+    cache: ElectionsCache,
+}
+
+impl<P> CachedDataProvider<P> {
+    pub fn new(provider: P, cache: ElectionsCache) -> Self {
+        Self { provider, cache }
+    }
+
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    pub fn cache(&self) -> &ElectionsCache {
+        &self.cache
+    }
+}
+
+impl<P: OnchainElectionsDataProvider> OnchainElectionsDataProvider for CachedDataProvider<P> {
+    async fn elections_at_blockhash(&self, hash: Option<Hash>) -> Result<OnchainElectionsData> {
+        // Only data at explicit block hashes is immutable
+        let Some(hash) = hash else {
+            return self.provider.elections_at_blockhash(None).await;
+        };
+        if let Some(data) = self.cache.elections_data(&hash) {
+            event!(Level::DEBUG, "Elections data at {:?} found in cache", hash);
+            return Ok(data);
+        }
+
+        let data = self.provider.elections_at_blockhash(Some(hash)).await?;
+        self.cache.insert_elections_data(&data);
+
+        Ok(data)
+    }
+
+    async fn parent_blockhash(&self, hash: Hash) -> Result<Hash> {
+        self.provider.parent_blockhash(hash).await
+    }
+
+    async fn blockhash_at(&self, number: u32) -> Result<Hash> {
+        self.provider.blockhash_at(number).await
+    }
+
+    async fn latest_blockhash(&self, latest: LatestBlock) -> Result<Hash> {
+        self.provider.latest_blockhash(latest).await
+    }
+}
+
+impl<P: OnchainIdentityProvider> OnchainIdentityProvider for CachedDataProvider<P> {
+    async fn map_elections_identities(
+        &self,
+        elections: &mut ApiElectionData,
+        at: Hash,
+        include_current: bool,
+    ) -> Result<()> {
+        self.provider
+            .map_elections_identities(elections, at, include_current)
+            .await
+    }
+}
+
+/// Data provider shared by API workers
+pub type AppDataProvider = CachedDataProvider<DataProvider>;
@@ -0,0 +1,202 @@
+use super::*;
+use actix_web::web::Bytes;
+use jsonrpsee::server::{RpcModule, Server, ServerHandle};
+use jsonrpsee::types::{ErrorObjectOwned, Params};
+use serde_json::Value;
+use serde_json::value::RawValue;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use subxt::backend::rpc::{RawRpcFuture, RawRpcSubscription, RpcClient, RpcClientT};
+
+/// JSON-RPC traffic recorded for elections data at a block, with the API response
+#[derive(Serialize, Deserialize)]
+pub struct RpcFixture {
+    /// Block hash of recorded elections data
+    #[serde(rename = "blockHash")]
+    pub block_hash: Hash,
+    /// Recorded requests with their results
+    pub exchanges: Vec<RpcExchange>,
+    /// Response of `/council/elections/{blockHash}` at the time of recording
+    pub response: Value,
+}
+
+/// Single recorded JSON-RPC request
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RpcExchange {
+    pub method: String,
+    pub params: Value,
+    pub result: Value,
+}
+
+impl RpcFixture {
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = std::fs::File::open(path)?;
+
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+
+        Ok(())
+    }
+}
+
+/// JSON-RPC client recording results of requests (subscriptions are not recorded)
+#[derive(Clone)]
+pub struct RecordingRpcClient<R> {
+    inner: R,
+    exchanges: Arc<Mutex<Vec<RpcExchange>>>,
+}
+
+impl<R> RecordingRpcClient<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            exchanges: Default::default(),
+        }
+    }
+
+    /// Requests recorded so far
+    pub fn exchanges(&self) -> Vec<RpcExchange> {
+        let exchanges = self.exchanges.lock().unwrap_or_else(|e| e.into_inner());
+        exchanges.clone()
+    }
+}
+
+impl<R: RpcClientT> RpcClientT for RecordingRpcClient<R> {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<RawValue>>,
+    ) -> RawRpcFuture<'a, Box<RawValue>> {
+        Box::pin(async move {
+            let params_value = params_value(params.as_deref().map(RawValue::get));
+            let result = self.inner.request_raw(method, params).await?;
+
+            let mut exchanges = self.exchanges.lock().unwrap_or_else(|e| e.into_inner());
+            exchanges.push(RpcExchange {
+                method: method.to_string(),
+                params: params_value,
+                result: serde_json::from_str(result.get()).unwrap_or(Value::Null),
+            });
+
+            Ok(result)
+        })
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        params: Option<Box<RawValue>>,
+        unsub: &'a str,
+    ) -> RawRpcFuture<'a, RawRpcSubscription> {
+        self.inner.subscribe_raw(sub, params, unsub)
+    }
+}
+
+/// Local JSON-RPC server answering recorded requests (stopped when dropped)
+pub struct ReplayServer {
+    addr: SocketAddr,
+    _handle: ServerHandle,
+}
+
+impl ReplayServer {
+    /// Serve recorded requests on given address (port 0 picks a free port).
+    /// Nodes are always reported healthy, other requests fail unless recorded.
+    pub async fn start(exchanges: &[RpcExchange], addr: SocketAddr) -> Result<Self> {
+        let mut results: HashMap<String, HashMap<String, Value>> = HashMap::new();
+        for exchange in exchanges {
+            results
+                .entry(exchange.method.clone())
+                .or_default()
+                .insert(exchange.params.to_string(), exchange.result.clone());
+        }
+
+        let mut module = RpcModule::new(());
+        module.register_method(
+            "system_health",
+            |_, _, _| serde_json::json!({"peers": 1, "isSyncing": false, "shouldHavePeers": true}),
+        )?;
+        for (method, results) in results {
+            // Method names are registered for the lifetime of the process
+            let name: &'static str = Box::leak(method.into_boxed_str());
+            module.register_method(name, move |params: Params, _, _| {
+                let params = params_value(params.as_str());
+                results.get(&params.to_string()).cloned().ok_or_else(|| {
+                    ErrorObjectOwned::owned(
+                        -32000,
+                        format!("Request not recorded: {}({})", name, params),
+                        None::<()>,
+                    )
+                })
+            })?;
+        }
+
+        let server = Server::builder().build(addr).await?;
+        let addr = server.local_addr()?;
+        let handle = server.start(module);
+        event!(
+            Level::INFO,
+            "Replaying recorded JSON-RPC requests at ws://{}",
+            addr
+        );
+
+        Ok(Self {
+            addr,
+            _handle: handle,
+        })
+    }
+
+    /// WebSocket URL of the server
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+}
+
+/// Record JSON-RPC traffic of elections data request at block hash
+pub async fn record_fixture(
+    urls: &[String],
+    rpc_timeout: Duration,
+    block_hash: Hash,
+) -> Result<RpcFixture> {
+    let recorder = RecordingRpcClient::new(FailoverRpcClient::new(urls, rpc_timeout)?);
+    let onchain = OnchainDataProvider::from_rpc_client(
+        RpcClient::new(recorder.clone()),
+        Duration::from_secs(0),
+    )
+    .await?;
+
+    let body = elections_response(DataProvider::Onchain(onchain), block_hash).await?;
+
+    Ok(RpcFixture {
+        block_hash,
+        exchanges: recorder.exchanges(),
+        response: serde_json::from_slice(&body)?,
+    })
+}
+
+/// Body of `/council/elections/{blockHash}` response served from given data provider
+/// (no caching on disk)
+pub async fn elections_response(data_provider: DataProvider, block_hash: Hash) -> Result<Bytes> {
+    let data_provider = CachedDataProvider::new(data_provider, ElectionsCache::new(1, None, 1)?);
+    let body = elections_json(
+        &data_provider,
+        &ElectionsInFlight::default(),
+        Some(block_hash),
+        &ApiElectionsQuery::default(),
+    )
+    .await?;
+
+    Ok(body)
+}
+
+/// Parameters of a request as JSON value (null if none)
+fn params_value(params: Option<&str>) -> Value {
+    params
+        .and_then(|params| serde_json::from_str(params).ok())
+        .unwrap_or(Value::Null)
+}
@@ -0,0 +1,300 @@
+use super::*;
+use serde_json::value::RawValue;
+use std::sync::Arc;
+use subxt::backend::rpc::{RawRpcFuture, RawRpcSubscription, RpcClient, RpcClientT};
+use subxt::error::RpcError;
+use tokio::sync::Mutex;
+
+/// Delay before reconnecting after all endpoints failed (doubled on each failed round)
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+/// Maximum delay between reconnection rounds
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// JSON-RPC client switching between several endpoints.
+///
+/// Endpoints are connected lazily and health-checked (`system_health`) before use.
+/// Requests failing on transport errors or timeouts are retried on the next endpoint.
+/// Once all endpoints failed, requests fail without waiting until the next reconnection
+/// round, scheduled with exponential backoff.
+#[derive(Clone)]
+pub struct FailoverRpcClient {
+    urls: Arc<Vec<String>>,
+    timeout: Duration,
+    state: Arc<Mutex<FailoverState>>,
+    /// Held during reconnection rounds, so that concurrent requests share them
+    /// (the state is only locked briefly, between connection attempts)
+    reconnecting: Arc<Mutex<()>>,
+}
+
+#[derive(Default)]
+struct FailoverState {
+    /// Index of endpoint in use (or to be tried first)
+    endpoint: usize,
+    /// Connection to the endpoint in use
+    client: Option<RpcClient>,
+    /// Incremented on every connection, so that failures of old connections are ignored
+    generation: u64,
+    /// Earliest time of the next reconnection round, after all endpoints failed
+    retry_at: Option<tokio::time::Instant>,
+    /// Delay before the round following a failed one (zero until a round fails)
+    delay: Duration,
+}
+
+impl FailoverState {
+    /// Connection in use, or error while backing off (None if a round may start)
+    fn current(&self) -> Option<Result<(u64, RpcClient), RpcError>> {
+        if let Some(client) = &self.client {
+            return Some(Ok((self.generation, client.clone())));
+        }
+
+        let retry_in = self
+            .retry_at?
+            .checked_duration_since(tokio::time::Instant::now())?;
+        Some(Err(RpcError::Client(
+            format!(
+                "All RPC endpoints unavailable, reconnecting in {:?}",
+                retry_in
+            )
+            .into(),
+        )))
+    }
+}
+
+impl FailoverRpcClient {
+    pub fn new(urls: &[String], timeout: Duration) -> Result<Self> {
+        if urls.is_empty() {
+            anyhow::bail!("At least one RPC endpoint is required (pass --url)");
+        }
+
+        Ok(Self {
+            urls: Arc::new(urls.to_vec()),
+            timeout,
+            state: Default::default(),
+            reconnecting: Default::default(),
+        })
+    }
+
+    /// Connection to the endpoint in use, connecting first if needed.
+    /// Every endpoint is tried once, then requests fail until the next round is due.
+    async fn connected(&self) -> Result<(u64, RpcClient), RpcError> {
+        if let Some(current) = self.state.lock().await.current() {
+            return current;
+        }
+
+        // Requests waiting for a round in progress get its outcome
+        let _reconnecting = self.reconnecting.lock().await;
+        let first = {
+            let state = self.state.lock().await;
+            if let Some(current) = state.current() {
+                return current;
+            }
+            state.endpoint
+        };
+
+        for offset in 0..self.urls.len() {
+            let endpoint = (first + offset) % self.urls.len();
+            let url = &self.urls[endpoint];
+            match self.connect(url).await {
+                Ok(client) => {
+                    event!(Level::INFO, "Connected to RPC endpoint {}", url);
+                    let mut state = self.state.lock().await;
+                    state.endpoint = endpoint;
+                    state.client = Some(client.clone());
+                    state.generation += 1;
+                    state.retry_at = None;
+                    state.delay = Duration::ZERO;
+                    return Ok((state.generation, client));
+                }
+                Err(e) => event!(Level::WARN, "RPC endpoint {} unavailable: {}", url, e),
+            }
+        }
+
+        let mut state = self.state.lock().await;
+        let delay = state.delay.max(RECONNECT_INITIAL_DELAY);
+        event!(
+            Level::WARN,
+            "All RPC endpoints unavailable, reconnecting in {:?}",
+            delay
+        );
+        state.retry_at = Some(tokio::time::Instant::now() + delay);
+        state.delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+
+        Err(RpcError::Client(
+            "All RPC endpoints unavailable".to_string().into(),
+        ))
+    }
+
+    /// Connect to endpoint and check that it's healthy (not syncing)
+    async fn connect(&self, url: &str) -> Result<RpcClient> {
+        let client = tokio::time::timeout(self.timeout, RpcClient::from_url(url))
+            .await
+            .map_err(|_| anyhow::anyhow!("connection timed out"))??;
+
+        let health = tokio::time::timeout(self.timeout, client.request_raw("system_health", None))
+            .await
+            .map_err(|_| anyhow::anyhow!("system_health timed out"))??;
+        let health: serde_json::Value = serde_json::from_str(health.get())?;
+        if health["isSyncing"].as_bool().unwrap_or(false) {
+            anyhow::bail!("node is syncing");
+        }
+
+        Ok(client)
+    }
+
+    /// Drop failed connection and switch to the next endpoint
+    async fn disconnect(&self, generation: u64) {
+        let mut state = self.state.lock().await;
+        if state.generation == generation && state.client.is_some() {
+            state.client = None;
+            state.endpoint = (state.endpoint + 1) % self.urls.len();
+        }
+    }
+
+    /// Run request on the endpoint in use, failing over to other endpoints on
+    /// transport errors and timeouts (errors returned by nodes are passed through)
+    async fn with_failover<'a, T, F>(&self, name: &str, request: F) -> Result<T, RpcError>
+    where
+        F: Fn(RpcClient) -> RawRpcFuture<'a, T> + Send + Sync,
+        T: Send,
+    {
+        let mut attempts = 0;
+        loop {
+            let (generation, client) = self.connected().await?;
+            let error = match tokio::time::timeout(self.timeout, request(client)).await {
+                Ok(Ok(value)) => return Ok(value),
+                Ok(Err(e)) if !is_connection_error(&e) => return Err(e),
+                Ok(Err(e)) => e,
+                Err(_) => RpcError::Client(format!("{} timed out", name).into()),
+            };
+
+            attempts += 1;
+            event!(
+                Level::WARN,
+                "{} failed ({}/{}): {}",
+                name,
+                attempts,
+                self.urls.len() + 1,
+                error
+            );
+            self.disconnect(generation).await;
+            if attempts > self.urls.len() {
+                return Err(error);
+            }
+        }
+    }
+}
+
+impl RpcClientT for FailoverRpcClient {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<RawValue>>,
+    ) -> RawRpcFuture<'a, Box<RawValue>> {
+        Box::pin(self.with_failover(method, move |client| {
+            let params = params.clone();
+            Box::pin(async move { client.request_raw(method, params).await })
+        }))
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        params: Option<Box<RawValue>>,
+        unsub: &'a str,
+    ) -> RawRpcFuture<'a, RawRpcSubscription> {
+        // Dropped subscriptions end with an error, and are resubscribed by their users
+        Box::pin(self.with_failover(sub, move |client| {
+            let params = params.clone();
+            Box::pin(async move { client.subscribe_raw(sub, params, unsub).await })
+        }))
+    }
+}
+
+/// Whether error is caused by connection (as opposed to errors returned by the node)
+pub fn is_connection_error(error: &RpcError) -> bool {
+    matches!(
+        error,
+        RpcError::Client(_) | RpcError::DisconnectedWillReconnect(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::server::{RpcModule, Server, ServerHandle};
+    use serde_json::{Value, json};
+    use subxt::backend::rpc::rpc_params;
+
+    /// Start a node answering `chain_getFinalizedHead` with `head` after `delay`
+    async fn node(head: &'static str, delay: Duration) -> (String, ServerHandle) {
+        let mut module = RpcModule::new(());
+        module
+            .register_method(
+                "system_health",
+                |_, _, _| json!({"peers": 1, "isSyncing": false, "shouldHavePeers": true}),
+            )
+            .unwrap();
+        module
+            .register_async_method("chain_getFinalizedHead", move |_, _, _| async move {
+                tokio::time::sleep(delay).await;
+                Value::from(head)
+            })
+            .unwrap();
+
+        let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+
+        (url, server.start(module))
+    }
+
+    async fn finalized_head(client: &RpcClient) -> Value {
+        client
+            .request("chain_getFinalizedHead", rpc_params![])
+            .await
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn failover_rotates_endpoint_after_timeout() {
+        let (slow, _slow) = node("0x01", Duration::from_secs(5)).await;
+        let (fast, _fast) = node("0x02", Duration::ZERO).await;
+        let failover = FailoverRpcClient::new(&[slow, fast], Duration::from_millis(200)).unwrap();
+        let client = RpcClient::new(failover.clone());
+
+        assert_eq!(finalized_head(&client).await, json!("0x02"));
+        assert_eq!(failover.state.lock().await.endpoint, 1);
+    }
+
+    #[actix_web::test]
+    async fn failover_rotates_endpoint_after_disconnect() {
+        let (first, first_handle) = node("0x01", Duration::ZERO).await;
+        let (second, _second) = node("0x02", Duration::ZERO).await;
+        let failover = FailoverRpcClient::new(&[first, second], Duration::from_secs(5)).unwrap();
+        let client = RpcClient::new(failover.clone());
+        assert_eq!(finalized_head(&client).await, json!("0x01"));
+
+        first_handle.stop().unwrap();
+        first_handle.stopped().await;
+
+        assert_eq!(finalized_head(&client).await, json!("0x02"));
+        assert_eq!(failover.state.lock().await.endpoint, 1);
+    }
+
+    #[actix_web::test]
+    async fn failover_fails_fast_while_backing_off() {
+        let (url, handle) = node("0x01", Duration::ZERO).await;
+        handle.stop().unwrap();
+        handle.stopped().await;
+        let failover = FailoverRpcClient::new(&[url], Duration::from_millis(200)).unwrap();
+        let client = RpcClient::new(failover.clone());
+        let request = || client.request::<Value>("chain_getFinalizedHead", rpc_params![]);
+
+        assert!(request().await.is_err());
+
+        // Endpoints are not retried before the backoff delay elapsed
+        let started = tokio::time::Instant::now();
+        assert!(request().await.is_err());
+        assert!(started.elapsed() < RECONNECT_INITIAL_DELAY);
+        assert!(failover.state.lock().await.retry_at.is_some());
+    }
+}
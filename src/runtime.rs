@@ -0,0 +1,124 @@
+use super::*;
+use std::sync::OnceLock;
+use subxt::Metadata;
+use subxt::ext::codec::Decode;
+use subxt::ext::scale_decode::DecodeAsType;
+use subxt::utils::Yes;
+
+/// Metadata artifact the code is generated from
+pub const BUNDLED_METADATA: &[u8] = include_bytes!("../artifacts/mainnet.scale");
+
+/// Runtime the code is generated from
+pub struct BundledRuntime {
+    pub metadata: Metadata,
+    pub spec_version: u32,
+    pub metadata_hash: [u8; 32],
+}
+
+/// Runtime of the bundled metadata artifact
+pub fn bundled_runtime() -> &'static BundledRuntime {
+    static BUNDLED: OnceLock<BundledRuntime> = OnceLock::new();
+    BUNDLED.get_or_init(|| {
+        let metadata = Metadata::decode(&mut &BUNDLED_METADATA[..])
+            .expect("Bundled metadata artifact is invalid");
+        // `System::Version` starts with spec name, impl name, authoring version and spec version
+        let spec_version = metadata
+            .pallet_by_name("System")
+            .and_then(|pallet| pallet.constant_by_name("Version"))
+            .and_then(|version| {
+                <(String, String, u32, u32)>::decode(&mut version.value())
+                    .ok()
+                    .map(|(_, _, _, spec_version)| spec_version)
+            })
+            .expect("Bundled metadata artifact has no runtime version");
+        let metadata_hash = metadata.hasher().hash();
+
+        BundledRuntime {
+            metadata,
+            spec_version,
+            metadata_hash,
+        }
+    })
+}
+
+/// Client decoding storage of a runtime
+#[derive(Clone)]
+pub struct RuntimeClient {
+    /// Client using metadata of the runtime
+    pub api: OnlineClient<SubstrateConfig>,
+    /// Whether runtime metadata matches the bundled artifact (so that generated types
+    /// can be used, instead of dynamic decoding)
+    pub compatible: bool,
+}
+
+impl RuntimeClient {
+    /// Fetch storage value at block, decoding it with generated types if runtime is
+    /// compatible, or dynamically otherwise
+    pub async fn fetch<A>(&self, block_hash: Hash, address: &A) -> Result<Option<A::Target>>
+    where
+        A: subxt::storage::Address<IsFetchable = Yes>,
+        A::Target: DecodeAsType,
+    {
+        let storage = self.api.storage().at(block_hash);
+        if self.compatible {
+            return Ok(storage.fetch(address).await?);
+        }
+
+        let (pallet, entry) = (address.pallet_name(), address.entry_name());
+        let value = storage
+            .fetch(&subxt::dynamic::storage(pallet, entry, ()))
+            .await?
+            .map(|value| value.as_type::<A::Target>())
+            .transpose()
+            .map_err(|e| Error::Decode(format!("{}::{}: {}", pallet, entry, e)))?;
+
+        Ok(value)
+    }
+
+    /// Constant value, decoded with generated types if runtime is compatible,
+    /// or dynamically otherwise
+    pub fn constant<A>(&self, address: &A) -> Result<A::Target>
+    where
+        A: subxt::constants::Address,
+        A::Target: DecodeAsType,
+    {
+        if self.compatible {
+            return Ok(self.api.constants().at(address)?);
+        }
+
+        let (pallet, constant) = (address.pallet_name(), address.constant_name());
+        let value = self
+            .api
+            .constants()
+            .at(&subxt::dynamic::constant(pallet, constant))?
+            .as_type::<A::Target>()
+            .map_err(|e| Error::Decode(format!("{}::{}: {}", pallet, constant, e)))?;
+
+        Ok(value)
+    }
+
+    /// Decode value of a storage map entry (with generated types if runtime is
+    /// compatible, or dynamically otherwise)
+    pub fn decode_value<T: Decode + DecodeAsType>(
+        &self,
+        pallet: &str,
+        entry: &str,
+        value: &[u8],
+    ) -> Result<T> {
+        if self.compatible {
+            return T::decode(&mut &value[..])
+                .map_err(|e| Error::Decode(format!("{}::{} value: {}", pallet, entry, e)).into());
+        }
+
+        let metadata = self.api.metadata();
+        let type_id = metadata
+            .pallet_by_name(pallet)
+            .and_then(|pallet| pallet.storage())
+            .and_then(|storage| storage.entry_by_name(entry))
+            .map(|entry| entry.entry_type().value_ty())
+            .ok_or_else(|| Error::StorageMissing(format!("{}::{} in metadata", pallet, entry)))?;
+
+        T::decode_as_type(&mut &value[..], type_id, metadata.types())
+            .map_err(|e| Error::Decode(format!("{}::{} value: {}", pallet, entry, e)).into())
+    }
+}
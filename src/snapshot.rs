@@ -0,0 +1,241 @@
+use super::*;
+use std::path::{Path, PathBuf};
+
+/// Snapshot file format
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SnapshotFormat {
+    /// SCALE encoded
+    #[default]
+    Scale,
+    /// JSON
+    Json,
+}
+
+impl SnapshotFormat {
+    const ALL: [SnapshotFormat; 2] = [SnapshotFormat::Scale, SnapshotFormat::Json];
+
+    /// File extension of snapshots in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SnapshotFormat::Scale => "scale",
+            SnapshotFormat::Json => "json",
+        }
+    }
+
+    /// Detect format from file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+}
+
+/// Version of JSON snapshots
+const JSON_SNAPSHOT_VERSION: u8 = 1;
+
+/// JSON snapshot file
+#[derive(Serialize, Deserialize)]
+struct JsonSnapshot<T> {
+    version: u8,
+    data: T,
+}
+
+/// Version of JSON snapshot file (None for unversioned snapshots of older releases,
+/// which contain elections data only)
+#[derive(Deserialize)]
+struct JsonSnapshotVersion {
+    version: Option<u8>,
+}
+
+/// File name of snapshot taken at given block
+pub fn snapshot_file_name(block_hash: &Hash, format: SnapshotFormat) -> String {
+    format!("{:?}.{}", block_hash, format.extension())
+}
+
+/// Write elections data snapshot to file
+pub async fn write_snapshot(
+    data: &OnchainElectionsData,
+    path: &Path,
+    format: SnapshotFormat,
+) -> Result<()> {
+    let bytes = match format {
+        SnapshotFormat::Scale => data.encode_versioned(),
+        SnapshotFormat::Json => serde_json::to_vec_pretty(&JsonSnapshot {
+            version: JSON_SNAPSHOT_VERSION,
+            data,
+        })?,
+    };
+    tokio::fs::write(path, bytes).await?;
+
+    Ok(())
+}
+
+/// Read elections data snapshot from file (format is detected from file extension)
+pub async fn read_snapshot(path: &Path) -> Result<OnchainElectionsData> {
+    let format = SnapshotFormat::from_path(path)
+        .ok_or_else(|| anyhow::anyhow!("Unknown snapshot format: {}", path.display()))?;
+    let bytes = tokio::fs::read(path).await?;
+    let data = match format {
+        SnapshotFormat::Scale => OnchainElectionsData::decode_versioned(&bytes)?,
+        SnapshotFormat::Json => read_json_snapshot(&bytes, path)?,
+    };
+
+    Ok(data)
+}
+
+/// Parse JSON snapshot, of current or unversioned format
+fn read_json_snapshot(bytes: &[u8], path: &Path) -> Result<OnchainElectionsData> {
+    let version: JsonSnapshotVersion = serde_json::from_slice(bytes)?;
+    match version.version {
+        Some(JSON_SNAPSHOT_VERSION) => {
+            let snapshot: JsonSnapshot<OnchainElectionsData> = serde_json::from_slice(bytes)?;
+            Ok(snapshot.data)
+        }
+        Some(version) => Err(Error::Decode(format!(
+            "Unsupported snapshot version {} in {} (expected {})",
+            version,
+            path.display(),
+            JSON_SNAPSHOT_VERSION
+        ))
+        .into()),
+        None => {
+            let data: OnchainElectionsData = serde_json::from_slice(bytes)?;
+            if data.voting_integrity.is_none() {
+                event!(
+                    Level::WARN,
+                    "Snapshot {} predates voting integrity records, its voter set can't be verified",
+                    path.display()
+                );
+            }
+            Ok(data)
+        }
+    }
+}
+
+/// Download elections data at given block and store it in `dir`
+pub async fn export_snapshot(
+    onchain: &OnchainDataProvider<SubstrateConfig>,
+    block_hash: Option<Hash>,
+    dir: &Path,
+    format: SnapshotFormat,
+) -> Result<PathBuf> {
+    let data = onchain.elections_at_blockhash(block_hash).await?;
+    let path = dir.join(snapshot_file_name(&data.block_hash, format));
+    write_snapshot(&data, &path, format).await?;
+
+    event!(
+        Level::INFO,
+        "Elections data at block hash {:?} written to {}",
+        data.block_hash,
+        path.display()
+    );
+
+    Ok(path)
+}
+
+/// Provides elections data from snapshot files, without connecting to a node
+#[derive(Clone)]
+pub struct SnapshotDataProvider {
+    dir: PathBuf,
+}
+
+impl SnapshotDataProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        if !dir.is_dir() {
+            anyhow::bail!("Snapshot directory not found: {}", dir.display());
+        }
+
+        Ok(Self { dir })
+    }
+
+    /// Path of snapshot taken at given block (in any format)
+    fn snapshot_path(&self, block_hash: &Hash) -> Option<PathBuf> {
+        SnapshotFormat::ALL
+            .into_iter()
+            .map(|format| self.dir.join(snapshot_file_name(block_hash, format)))
+            .find(|path| path.is_file())
+    }
+
+    /// Path of the most recently written snapshot
+    async fn latest_snapshot_path(&self) -> Result<PathBuf> {
+        let mut latest: Option<(std::time::SystemTime, PathBuf)> = None;
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if SnapshotFormat::from_path(&path).is_none() {
+                continue;
+            }
+            let modified = entry.metadata().await?.modified()?;
+            if latest.as_ref().is_none_or(|(time, _)| modified > *time) {
+                latest = Some((modified, path));
+            }
+        }
+
+        latest.map(|(_, path)| path).ok_or_else(|| {
+            Error::BlockNotFound(format!("no snapshots in {}", self.dir.display())).into()
+        })
+    }
+}
+
+impl OnchainElectionsDataProvider for SnapshotDataProvider {
+    async fn elections_at_blockhash(&self, hash: Option<Hash>) -> Result<OnchainElectionsData> {
+        let path = match hash {
+            Some(hash) => self
+                .snapshot_path(&hash)
+                .ok_or_else(|| Error::BlockNotFound(format!("no snapshot for {:?}", hash)))?,
+            None => self.latest_snapshot_path().await?,
+        };
+        event!(
+            Level::DEBUG,
+            "Reading election data from snapshot: {}",
+            path.display()
+        );
+
+        read_snapshot(&path).await
+    }
+
+    async fn parent_blockhash(&self, _hash: Hash) -> Result<Hash> {
+        // Block headers are not part of snapshots
+        Err(
+            Error::BadRequest("Parent block hashes are not available in snapshots".to_string())
+                .into(),
+        )
+    }
+
+    async fn blockhash_at(&self, number: u32) -> Result<Hash> {
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if SnapshotFormat::from_path(&path).is_none() {
+                continue;
+            }
+            let data = read_snapshot(&path).await?;
+            if data.block_number == number {
+                return Ok(data.block_hash);
+            }
+        }
+
+        Err(Error::BlockNotFound(format!("no snapshot for #{}", number)).into())
+    }
+
+    async fn latest_blockhash(&self, _latest: LatestBlock) -> Result<Hash> {
+        // Most recent snapshot is used for both finalized and best block
+        let path = self.latest_snapshot_path().await?;
+
+        Ok(read_snapshot(&path).await?.block_hash)
+    }
+}
+
+impl OnchainIdentityProvider for SnapshotDataProvider {
+    async fn map_elections_identities(
+        &self,
+        _elections: &mut ApiElectionData,
+        _at: Hash,
+        _include_current: bool,
+    ) -> Result<()> {
+        // Identities are not part of snapshots
+        Ok(())
+    }
+}
@@ -2,8 +2,18 @@ use super::*;
 
 pub trait OnchainElectionsDataProvider {
     async fn elections_at_blockhash(&self, hash: Option<Hash>) -> Result<OnchainElectionsData>;
+    async fn parent_blockhash(&self, hash: Hash) -> Result<Hash>;
+    async fn blockhash_at(&self, number: u32) -> Result<Hash>;
+    async fn latest_blockhash(&self, latest: LatestBlock) -> Result<Hash>;
 }
 
 pub trait OnchainIdentityProvider {
-    async fn map_elections_identities(&self, elections: &mut ApiElectionData) -> Result<()>;
+    /// Map addresses to identities at given block hash, optionally adding current
+    /// identities (at latest finalized block) as well
+    async fn map_elections_identities(
+        &self,
+        elections: &mut ApiElectionData,
+        at: Hash,
+        include_current: bool,
+    ) -> Result<()>;
 }
@@ -1,4 +1,7 @@
 use super::*;
+use sp_arithmetic::PerThing;
+use sp_npos_elections::Support;
+use std::collections::HashMap;
 
 /// Account data with optional display name
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -9,6 +12,91 @@ pub struct ApiAccount {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "displayName")]
     pub display_name: Option<String>,
+    /// Optional current identity display name (if requested)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "currentDisplayName")]
+    pub current_display_name: Option<String>,
+    /// Optional on-chain identity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<ApiIdentity>,
+}
+
+/// On-chain identity record
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ApiIdentity {
+    /// Display name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<ApiIdentityData>,
+    /// Legal name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub legal: Option<ApiIdentityData>,
+    /// Website
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web: Option<ApiIdentityData>,
+    /// Email address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<ApiIdentityData>,
+    /// Twitter handle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub twitter: Option<ApiIdentityData>,
+    /// Judgements given by registrars
+    pub judgements: Vec<ApiJudgement>,
+    /// Parent account (for sub-accounts, whose identity is taken from the parent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<ApiParentAccount>,
+}
+
+impl ApiIdentity {
+    /// Display name, if set as raw data ("Parent/sub" for sub-accounts)
+    pub fn display_name(&self) -> Option<String> {
+        let display_name = match &self.display {
+            Some(ApiIdentityData::Raw(display_name)) => Some(display_name.clone()),
+            _ => None,
+        };
+
+        match self.parent.as_ref().map(|parent| &parent.sub_name) {
+            Some(Some(ApiIdentityData::Raw(sub_name))) => display_name
+                .map(|display_name| format!("{}/{}", display_name, sub_name))
+                .or_else(|| Some(sub_name.clone())),
+            _ => display_name,
+        }
+    }
+}
+
+/// Parent of a sub-account
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiParentAccount {
+    /// Parent account address
+    pub address: String,
+    /// Name of the sub-account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "subName")]
+    pub sub_name: Option<ApiIdentityData>,
+}
+
+/// Identity field value (raw data is decoded as lossy UTF-8, hashes as hex)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", content = "value")]
+pub enum ApiIdentityData {
+    #[serde(rename = "raw")]
+    Raw(String),
+    #[serde(rename = "blakeTwo256")]
+    BlakeTwo256(String),
+    #[serde(rename = "sha256")]
+    Sha256(String),
+    #[serde(rename = "keccak256")]
+    Keccak256(String),
+    #[serde(rename = "shaThree256")]
+    ShaThree256(String),
+}
+
+/// Judgement given by a registrar
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiJudgement {
+    /// Registrar index
+    pub registrar: u32,
+    /// Judgement (unknown, feePaid, reasonable, knownGood, outOfDate, lowQuality, erroneous)
+    pub judgement: String,
 }
 
 /// Complete election data
@@ -17,12 +105,22 @@ pub struct ApiElectionData {
     /// Block hash where this election data was taken from
     #[serde(rename = "blockHash")]
     pub block_hash: String,
+    /// Block number where this election data was taken from
+    #[serde(rename = "blockNumber")]
+    pub block_number: u32,
+    /// Block timestamp (milliseconds since Unix epoch)
+    pub timestamp: u64,
     /// Number of elections commenced so far
     #[serde(rename = "electionRounds")]
     pub election_rounds: u32,
+    /// Algorithm used for simulation
+    pub algorithm: Algorithm,
     /// Council configuration
     #[serde(rename = "councilSeats")]
     pub council_seats: ApiCouncilSeats,
+    /// Conversion between balances and vote weights used by the simulation
+    #[serde(rename = "currencyToVote")]
+    pub currency_to_vote: ApiCurrencyToVote,
     /// Results for all candidates
     #[serde(rename = "finalResults")]
     pub final_results: Vec<ApiCandidateResult>,
@@ -30,8 +128,86 @@ pub struct ApiElectionData {
     pub candidates: Vec<ApiCandidate>,
     /// Voters with their stakes and votes
     pub voters: Vec<ApiVoter>,
+    /// Completeness of downloaded voter set
+    pub integrity: ApiIntegrity,
     /// Detailed rounds of the Phragmen algorithm
     pub rounds: Vec<ApiRound>,
+    /// Balancing applied to the solution (if requested)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balancing: Option<ApiBalancing>,
+    /// Voters merged by parent identity (if requested)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Vec<ApiEntity>>,
+}
+
+/// Query parameters accepted by elections endpoints
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ApiElectionsQuery {
+    /// Algorithm used for simulation (defaults to seqphragmen)
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    /// Maximum number of balancing iterations (balancing is disabled if not set)
+    #[serde(rename = "balancingIterations")]
+    pub balancing_iterations: Option<usize>,
+    /// Balancing tolerance, in balance units (defaults to 0)
+    #[serde(rename = "balancingTolerance")]
+    pub balancing_tolerance: Option<u64>,
+    /// Also return current display names, next to those at the queried block
+    #[serde(default)]
+    #[serde(rename = "currentIdentities")]
+    pub current_identities: bool,
+    /// Also return voters merged by parent identity
+    #[serde(default)]
+    #[serde(rename = "mergeByParent")]
+    pub merge_by_parent: bool,
+    /// Block used by latest elections data (defaults to finalized)
+    #[serde(default)]
+    pub at: LatestBlock,
+}
+
+impl ApiElectionsQuery {
+    /// Key identifying simulation results of this query at a given block
+    pub fn cache_key(&self) -> String {
+        format!(
+            "{:?}-{:?}-{:?}-{}",
+            self.algorithm,
+            self.balancing_iterations,
+            self.balancing_tolerance,
+            self.merge_by_parent
+        )
+    }
+
+    /// Whether only default parameters are used (as for precomputed latest elections data)
+    pub fn is_default(&self) -> bool {
+        self.cache_key() == Self::default().cache_key()
+            && !self.current_identities
+            && self.at == LatestBlock::Finalized
+    }
+
+    /// Simulation parameters requested by the query
+    pub fn simulation_params(&self) -> SimulationParams {
+        SimulationParams {
+            algorithm: self.algorithm,
+            balancing: self.balancing_iterations.map(|iterations| BalancingConfig {
+                iterations,
+                tolerance: self.balancing_tolerance.unwrap_or_default() as ExtendedBalance,
+            }),
+        }
+    }
+}
+
+/// Integrity record of voter set used by simulation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiIntegrity {
+    /// Number of enumerated `Voting` storage keys (null if not recorded)
+    #[serde(rename = "voterKeys")]
+    pub voter_keys: Option<u32>,
+    /// Number of voters used by simulation
+    pub voters: u32,
+    /// Number of storage pages enumerated
+    pub pages: u32,
+    /// Number of retried storage requests
+    pub retries: u32,
 }
 
 /// Council seats configuration
@@ -44,6 +220,16 @@ pub struct ApiCouncilSeats {
     pub runners_up: u32,
 }
 
+/// Balance to vote weight conversion parameters
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiCurrencyToVote {
+    /// Total issuance at the given block
+    #[serde(rename = "totalIssuance")]
+    pub total_issuance: u128,
+    /// Factor by which balances are divided to obtain vote weights
+    pub factor: u128,
+}
+
 /// Role in election
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ApiCandidateRole {
@@ -68,6 +254,8 @@ pub struct ApiCandidateResult {
     /// Final applied stake
     #[serde(rename = "finalStake")]
     pub final_stake: u128,
+    /// Voters backing this candidate (only for elected candidates)
+    pub backers: Vec<ApiBacker>,
 }
 
 /// Basic candidate information
@@ -87,8 +275,135 @@ pub struct ApiVoter {
     pub id: ApiAccount,
     /// Total stake
     pub stake: u128,
+    /// Stake converted to vote weight, as used by Phragmen
+    #[serde(rename = "voteWeight")]
+    pub vote_weight: u64,
+    /// Part of the stake lost in conversion to vote weight
+    #[serde(rename = "precisionLoss")]
+    pub precision_loss: u128,
     /// Votes cast for candidates
     pub votes: Vec<ApiAccount>,
+    /// Final distribution of stake among elected candidates
+    pub distribution: Vec<ApiVoterAssignment>,
+}
+
+/// Voters belonging to the same entity (parent identity with its sub-accounts)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiEntity {
+    /// Parent account (or voter account if it has no parent)
+    pub id: ApiAccount,
+    /// Voter accounts of the entity
+    pub accounts: Vec<ApiAccount>,
+    /// Total stake of all accounts
+    pub stake: u128,
+    /// Total vote weight of all accounts
+    #[serde(rename = "voteWeight")]
+    pub vote_weight: u64,
+    /// Candidates voted for by any of the accounts
+    pub votes: Vec<ApiAccount>,
+    /// Final distribution of total stake among elected candidates
+    pub distribution: Vec<ApiVoterAssignment>,
+}
+
+impl ApiEntity {
+    /// Merge voters by parent identity (voters keep their order of first appearance)
+    pub fn group_voters(voters: &[ApiVoter]) -> Vec<ApiEntity> {
+        let mut entities: Vec<ApiEntity> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+
+        for voter in voters {
+            let id = Self::entity_account(&voter.id);
+            let position = *index.entry(id.address.clone()).or_insert_with(|| {
+                entities.push(ApiEntity {
+                    id,
+                    accounts: vec![],
+                    stake: 0,
+                    vote_weight: 0,
+                    votes: vec![],
+                    distribution: vec![],
+                });
+                entities.len() - 1
+            });
+            let entity = &mut entities[position];
+
+            entity.accounts.push(voter.id.clone());
+            entity.stake = entity.stake.saturating_add(voter.stake);
+            entity.vote_weight = entity.vote_weight.saturating_add(voter.vote_weight);
+            for vote in &voter.votes {
+                if !entity.votes.iter().any(|v| v.address == vote.address) {
+                    entity.votes.push(vote.clone());
+                }
+            }
+            for assignment in &voter.distribution {
+                match entity
+                    .distribution
+                    .iter_mut()
+                    .find(|a| a.candidate.address == assignment.candidate.address)
+                {
+                    Some(existing) => {
+                        existing.stake = existing.stake.saturating_add(assignment.stake)
+                    }
+                    None => entity.distribution.push(assignment.clone()),
+                }
+            }
+        }
+
+        // Shares are relative to the total stake of the entity
+        for entity in entities.iter_mut() {
+            let stake = entity.stake;
+            for assignment in entity.distribution.iter_mut() {
+                assignment.share = if stake > 0 {
+                    assignment.stake as f64 / stake as f64
+                } else {
+                    0.0
+                };
+            }
+        }
+
+        entities
+    }
+
+    /// Account representing the entity of given voter account
+    fn entity_account(account: &ApiAccount) -> ApiAccount {
+        match account.identity.as_ref() {
+            Some(identity) => match identity.parent.as_ref() {
+                Some(parent) => {
+                    let identity = ApiIdentity {
+                        parent: None,
+                        ..identity.clone()
+                    };
+                    ApiAccount {
+                        address: parent.address.clone(),
+                        display_name: identity.display_name(),
+                        current_display_name: None,
+                        identity: Some(identity),
+                    }
+                }
+                None => account.clone(),
+            },
+            None => account.clone(),
+        }
+    }
+}
+
+/// Part of voter's stake backing an elected candidate
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiVoterAssignment {
+    /// Candidate account
+    pub candidate: ApiAccount,
+    /// Fraction of voter's stake (0.0 - 1.0)
+    pub share: f64,
+    /// Stake backing the candidate
+    pub stake: u128,
+}
+
+/// Voter backing an elected candidate
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiBacker {
+    /// Voter account
+    pub voter: ApiAccount,
+    /// Stake backing the candidate
+    pub stake: u128,
 }
 
 /// Information about a specific election round
@@ -127,6 +442,257 @@ pub struct ApiVoteDistribution {
     pub voter_count: usize,
 }
 
+/// Balancing configuration and its effects
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiBalancing {
+    /// Maximum number of iterations
+    pub iterations: usize,
+    /// Tolerance, in balance units (as requested, rounded down to a whole vote weight)
+    pub tolerance: u128,
+    /// Balancing runs (one after seq-Phragmen, one per round for PhragMMS)
+    pub runs: Vec<ApiBalancingRun>,
+}
+
+/// Single execution of the balancing algorithm
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiBalancingRun {
+    /// Round in which balancing was executed (none if after the whole election)
+    #[serde(rename = "roundNumber")]
+    pub round_number: Option<usize>,
+    /// Score before balancing
+    #[serde(rename = "scoreBefore")]
+    pub score_before: ApiElectionScore,
+    /// Score after balancing
+    #[serde(rename = "scoreAfter")]
+    pub score_after: ApiElectionScore,
+    /// Executed iterations
+    pub iterations: Vec<ApiBalancingIteration>,
+}
+
+/// Single balancing iteration
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiBalancingIteration {
+    /// Iteration number (1-based)
+    pub iteration: usize,
+    /// Biggest stake difference seen in this iteration (converted to balance)
+    #[serde(rename = "maxDiff")]
+    pub max_diff: u128,
+    /// Score after this iteration
+    pub score: ApiElectionScore,
+    /// Edges whose weight has changed
+    pub edges: Vec<ApiEdgeWeightUpdate>,
+}
+
+/// Change of stake a voter assigns to a candidate
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiEdgeWeightUpdate {
+    /// Voter account
+    pub voter: ApiAccount,
+    /// Candidate account
+    pub candidate: ApiAccount,
+    /// Stake before the change
+    pub stake: u128,
+    /// Stake after the change
+    #[serde(rename = "newStake")]
+    pub new_stake: u128,
+}
+
+/// Election score (converted to balance)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiElectionScore {
+    /// Backing stake of the least backed winner
+    #[serde(rename = "minimalStake")]
+    pub minimal_stake: u128,
+    /// Sum of backing stakes of all winners
+    #[serde(rename = "sumStake")]
+    pub sum_stake: u128,
+    /// Sum of squared backing stakes of all winners
+    #[serde(rename = "sumStakeSquared")]
+    pub sum_stake_squared: u128,
+}
+
+impl ApiElectionScore {
+    fn build_from(score: &ElectionScore, currency_to_vote: &CurrencyToVote) -> Self {
+        Self {
+            minimal_stake: currency_to_vote.to_currency(score.minimal_stake),
+            sum_stake: currency_to_vote.to_currency(score.sum_stake),
+            sum_stake_squared: currency_to_vote
+                .to_currency(currency_to_vote.to_currency(score.sum_stake_squared)),
+        }
+    }
+}
+
+impl ApiBalancing {
+    /// Build balancing report from Phragmen traces
+    fn build_from(config: &BalancingConfig, phragmen: &PhragmenOutputs) -> Self {
+        let currency_to_vote = &phragmen.currency_to_vote;
+        let mut runs = Vec::new();
+        let mut round_number = None;
+        let mut iterations = Vec::new();
+        for trace in &phragmen.traces {
+            match trace {
+                PhragmenTrace::RoundStart(round, _, _) => round_number = Some(*round),
+                PhragmenTrace::RoundEnd(_, _, _) => round_number = None,
+                PhragmenTrace::BalancingStart => iterations.clear(),
+                PhragmenTrace::BalancingIteration(update) => {
+                    iterations.push(ApiBalancingIteration {
+                        iteration: update.iteration,
+                        max_diff: currency_to_vote.to_currency(update.max_diff),
+                        score: ApiElectionScore::build_from(&update.score, currency_to_vote),
+                        edges: update
+                            .edges
+                            .iter()
+                            .map(|edge| ApiEdgeWeightUpdate {
+                                voter: ApiAccount::from(&edge.voter),
+                                candidate: ApiAccount::from(&edge.candidate),
+                                stake: currency_to_vote.to_currency(edge.weight),
+                                new_stake: currency_to_vote.to_currency(edge.new_weight),
+                            })
+                            .collect(),
+                    })
+                }
+                PhragmenTrace::ElectionScoreUpdated(update) => runs.push(ApiBalancingRun {
+                    round_number,
+                    score_before: ApiElectionScore::build_from(&update.score, currency_to_vote),
+                    score_after: ApiElectionScore::build_from(&update.new_score, currency_to_vote),
+                    iterations: std::mem::take(&mut iterations),
+                }),
+                _ => {}
+            }
+        }
+
+        Self {
+            iterations: config.iterations,
+            tolerance: currency_to_vote.to_currency(config.tolerance),
+            runs,
+        }
+    }
+}
+
+/// Result of PJR verification
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiPjrCheck {
+    /// Block hash where election data was taken from
+    #[serde(rename = "blockHash")]
+    pub block_hash: String,
+    /// Algorithm used for simulation
+    pub algorithm: Algorithm,
+    /// True if the simulated result satisfies PJR
+    pub satisfied: bool,
+    /// Standard PJR threshold
+    pub threshold: u128,
+    /// Candidate and voter group proving PJR violation
+    #[serde(rename = "counterExample")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counter_example: Option<ApiPjrCounterExample>,
+}
+
+/// Unelected candidate proving PJR violation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiPjrCounterExample {
+    /// Unelected candidate
+    pub candidate: ApiAccount,
+    /// Stake the voter group can spare for the candidate
+    #[serde(rename = "preScore")]
+    pub pre_score: u128,
+    /// Voters of the candidate with their spare stake
+    pub voters: Vec<ApiPjrVoter>,
+}
+
+/// Voter taking part in PJR counter example
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiPjrVoter {
+    /// Voter account
+    pub id: ApiAccount,
+    /// Stake this voter can spare without dropping any elected candidate below threshold
+    pub slack: u128,
+}
+
+impl ApiPjrCheck {
+    /// Build API response from PJR check outputs
+    pub fn build_from(onchain: &OnchainElectionsData, pjr: &PjrOutputs) -> Self {
+        let currency_to_vote = &pjr.currency_to_vote;
+        Self {
+            block_hash: format!("{:?}", onchain.block_hash),
+            algorithm: pjr.algorithm,
+            satisfied: pjr.satisfied,
+            threshold: currency_to_vote.to_currency(pjr.threshold),
+            counter_example: pjr.counter_example.as_ref().map(|counter_example| {
+                ApiPjrCounterExample {
+                    candidate: ApiAccount::from(&counter_example.candidate),
+                    pre_score: currency_to_vote.to_currency(counter_example.pre_score),
+                    voters: counter_example
+                        .voters
+                        .iter()
+                        .map(|(who, slack)| ApiPjrVoter {
+                            id: ApiAccount::from(who),
+                            slack: currency_to_vote.to_currency(*slack),
+                        })
+                        .collect(),
+                }
+            }),
+        }
+    }
+}
+
+impl ApiVoteDistribution {
+    /// Build distribution of stake among candidates elected so far
+    ///
+    /// Seq-Phragmen assigns edge weights only after the last round, so for it the stake applied is
+    /// derived from voter and edge loads, the same way the final edge weights are. PhragMMS updates
+    /// edge weights in every round, so these are used directly.
+    fn build_from(
+        candidates: &sp_npos_elections::Candidates<AccountId>,
+        voters: &sp_npos_elections::Voters<AccountId>,
+        algorithm: Algorithm,
+        currency_to_vote: &CurrencyToVote,
+    ) -> Vec<Self> {
+        use sp_arithmetic::{Rounding, helpers_128bit::multiply_by_rational_with_rounding};
+
+        // Candidates are snapshots, unlike the candidates pointed to by voter edges
+        let mut distribution: Vec<(AccountId, ExtendedBalance, usize)> = candidates
+            .iter()
+            .filter(|c| c.elected)
+            .map(|c| (c.who.clone(), 0, 0))
+            .collect();
+
+        for voter in voters {
+            for edge in &voter.edges {
+                let Some(entry) = distribution.iter_mut().find(|(who, _, _)| *who == edge.who)
+                else {
+                    continue;
+                };
+                let weight = match algorithm {
+                    Algorithm::SeqPhragmen if voter.load.n() > 0 => {
+                        multiply_by_rational_with_rounding(
+                            voter.budget,
+                            edge.load.n(),
+                            voter.load.n(),
+                            Rounding::Down,
+                        )
+                        .unwrap_or(ExtendedBalance::MAX)
+                    }
+                    Algorithm::SeqPhragmen => 0,
+                    Algorithm::Phragmms => edge.weight,
+                };
+                if weight > 0 {
+                    entry.1 = entry.1.saturating_add(weight);
+                    entry.2 += 1;
+                }
+            }
+        }
+
+        distribution
+            .into_iter()
+            .map(|(who, stake, voter_count)| Self {
+                candidate: ApiAccount::from(&who),
+                stake_applied: currency_to_vote.to_currency(stake),
+                voter_count,
+            })
+            .collect()
+    }
+}
+
 /// Helper methods for building API response
 impl ApiElectionData {
     /// Build API response from internal data structures
@@ -137,6 +703,9 @@ impl ApiElectionData {
             runners_up: onchain.desired_runners_up,
         };
 
+        // Conversion between balances and vote weights
+        let currency_to_vote = phragmen.currency_to_vote;
+
         // Process candidates
         let members: Vec<ApiCandidate> = onchain
             .members
@@ -167,14 +736,61 @@ impl ApiElectionData {
         candidates.append(&mut runners_up);
         candidates.append(&mut other_candidates);
 
+        // Process final assignments of voters (stakes are matched by voter and candidate,
+        // as staked assignments don't necessarily follow the order of assignments)
+        let staked_assignments: HashMap<&AccountId, &StakedAssignment<AccountId>> = phragmen
+            .staked_assignments
+            .iter()
+            .map(|staked| (&staked.who, staked))
+            .collect();
+        let mut distributions: HashMap<&AccountId, Vec<ApiVoterAssignment>> = HashMap::new();
+        for assignment in &phragmen.result.assignments {
+            let Some(staked) = staked_assignments.get(&assignment.who) else {
+                event!(
+                    Level::WARN,
+                    "No staked assignment for voter {}, distribution omitted",
+                    assignment.who
+                );
+                continue;
+            };
+            let distribution = assignment
+                .distribution
+                .iter()
+                .map(|(candidate, ratio)| {
+                    let stake = staked
+                        .distribution
+                        .iter()
+                        .find(|(staked_candidate, _)| staked_candidate == candidate)
+                        .map(|(_, stake)| *stake);
+                    if stake.is_none() {
+                        event!(
+                            Level::WARN,
+                            "No stake assigned by voter {} to candidate {}",
+                            assignment.who,
+                            candidate
+                        );
+                    }
+                    ApiVoterAssignment {
+                        candidate: ApiAccount::from(candidate),
+                        share: ratio.deconstruct() as f64 / Perbill::ACCURACY as f64,
+                        stake: currency_to_vote.to_currency(stake.unwrap_or_default()),
+                    }
+                })
+                .collect();
+            distributions.insert(&assignment.who, distribution);
+        }
+
         // Process voters
         let voters: Vec<ApiVoter> = onchain
             .voting
             .iter()
             .map(|(account_id, voter)| ApiVoter {
                 id: ApiAccount::from(account_id),
-                stake: voter.stake, // Adjust based on your Voter structure
+                stake: voter.stake,
+                vote_weight: currency_to_vote.to_vote(voter.stake),
+                precision_loss: currency_to_vote.precision_loss(voter.stake),
                 votes: voter.votes.iter().map(ApiAccount::from).collect(),
+                distribution: distributions.remove(account_id).unwrap_or_default(),
             })
             .collect();
 
@@ -207,6 +823,21 @@ impl ApiElectionData {
                     vote_distribution: vec![],
                 });
             }
+
+            // Distribution of stake at the end of each round
+            if let PhragmenTrace::RoundEnd(round_number, candidates, voters) = trace {
+                for round in rounds
+                    .iter_mut()
+                    .filter(|round| round.round_number == *round_number)
+                {
+                    round.vote_distribution = ApiVoteDistribution::build_from(
+                        candidates,
+                        voters,
+                        phragmen.algorithm,
+                        &currency_to_vote,
+                    );
+                }
+            }
         }
 
         // Build final results
@@ -217,16 +848,17 @@ impl ApiElectionData {
             .filter(|c_ptr| c_ptr.borrow().elected)
             .collect();
         elected_candidates.sort_by_key(|c_ptr| c_ptr.borrow().round);
-        let (elected_members, elected_runners_up) =
-            elected_candidates.split_at(council_seats.members as usize);
+        let split_point = (council_seats.members as usize).min(elected_candidates.len());
+        let (elected_members, elected_runners_up) = elected_candidates.split_at(split_point);
         let elected_members: Vec<ApiCandidateResult> = elected_members
             .iter()
             .map(|c_ptr| ApiCandidateResult {
                 id: ApiAccount::from(&c_ptr.borrow().who),
                 role: ApiCandidateRole::Member,
                 final_score: c_ptr.borrow().score.n() as f64 / c_ptr.borrow().score.d() as f64,
-                initial_stake: c_ptr.borrow().approval_stake,
-                final_stake: c_ptr.borrow().backed_stake,
+                initial_stake: currency_to_vote.to_currency(c_ptr.borrow().approval_stake),
+                final_stake: currency_to_vote.to_currency(c_ptr.borrow().backed_stake),
+                backers: vec![],
             })
             .collect();
         let mut elected_runners_up: Vec<ApiCandidateResult> = elected_runners_up
@@ -235,8 +867,9 @@ impl ApiElectionData {
                 id: ApiAccount::from(&c_ptr.borrow().who),
                 role: ApiCandidateRole::RunnerUp,
                 final_score: c_ptr.borrow().score.n() as f64 / c_ptr.borrow().score.d() as f64,
-                initial_stake: c_ptr.borrow().approval_stake,
-                final_stake: c_ptr.borrow().backed_stake,
+                initial_stake: currency_to_vote.to_currency(c_ptr.borrow().approval_stake),
+                final_stake: currency_to_vote.to_currency(c_ptr.borrow().backed_stake),
+                backers: vec![],
             })
             .collect();
 
@@ -252,8 +885,9 @@ impl ApiElectionData {
                 id: ApiAccount::from(&c_ptr.borrow().who),
                 role: ApiCandidateRole::NotElected,
                 final_score: c_ptr.borrow().score.n() as f64 / c_ptr.borrow().score.d() as f64,
-                initial_stake: c_ptr.borrow().approval_stake,
-                final_stake: c_ptr.borrow().backed_stake,
+                initial_stake: currency_to_vote.to_currency(c_ptr.borrow().approval_stake),
+                final_stake: currency_to_vote.to_currency(c_ptr.borrow().backed_stake),
+                backers: vec![],
             })
             .collect();
 
@@ -261,16 +895,306 @@ impl ApiElectionData {
         final_results.append(&mut elected_runners_up);
         final_results.append(&mut not_elected_candidates);
 
+        // Attach backers of elected candidates
+        let mut supports: HashMap<String, Support<AccountId>> =
+            sp_npos_elections::to_support_map(&phragmen.staked_assignments)
+                .into_iter()
+                .map(|(who, support)| (ss58_address(&who), support))
+                .collect();
+        for candidate in final_results.iter_mut() {
+            if let Some(support) = supports.remove(&candidate.id.address) {
+                candidate.backers = support
+                    .voters
+                    .iter()
+                    .map(|(voter, stake)| ApiBacker {
+                        voter: ApiAccount::from(voter),
+                        stake: currency_to_vote.to_currency(*stake),
+                    })
+                    .collect();
+            }
+        }
+
         Self {
             block_hash: format!("{:?}", onchain.block_hash),
+            block_number: onchain.block_number,
+            timestamp: onchain.timestamp,
             election_rounds: onchain.election_rounds,
+            algorithm: phragmen.algorithm,
             council_seats,
+            currency_to_vote: ApiCurrencyToVote {
+                total_issuance: currency_to_vote.total_issuance,
+                factor: currency_to_vote.factor(),
+            },
             final_results,
             candidates,
             voters,
+            integrity: ApiIntegrity {
+                voter_keys: onchain.voting_integrity.map(|integrity| integrity.keys),
+                voters: onchain.voting.len() as u32,
+                pages: onchain
+                    .voting_integrity
+                    .map_or(0, |integrity| integrity.pages),
+                retries: onchain
+                    .voting_integrity
+                    .map_or(0, |integrity| integrity.retries),
+            },
             rounds,
+            balancing: phragmen
+                .balancing
+                .as_ref()
+                .map(|config| ApiBalancing::build_from(config, phragmen)),
+            entities: None,
         }
     }
+
+    /// All accounts referenced in election data (e.g. for mapping identities)
+    pub fn accounts_mut(&mut self) -> Vec<&mut ApiAccount> {
+        let mut accounts: Vec<&mut ApiAccount> = Vec::new();
+
+        for candidate in self.final_results.iter_mut() {
+            accounts.push(&mut candidate.id);
+            accounts.extend(candidate.backers.iter_mut().map(|backer| &mut backer.voter));
+        }
+        accounts.extend(
+            self.candidates
+                .iter_mut()
+                .map(|candidate| &mut candidate.id),
+        );
+        for voter in self.voters.iter_mut() {
+            accounts.push(&mut voter.id);
+            accounts.extend(voter.votes.iter_mut());
+            accounts.extend(
+                voter
+                    .distribution
+                    .iter_mut()
+                    .map(|assignment| &mut assignment.candidate),
+            );
+        }
+        for round in self.rounds.iter_mut() {
+            accounts.extend(round.scores.iter_mut().map(|score| &mut score.id));
+            accounts.extend(
+                round
+                    .vote_distribution
+                    .iter_mut()
+                    .map(|distribution| &mut distribution.candidate),
+            );
+        }
+        for entity in self.entities.iter_mut().flatten() {
+            accounts.push(&mut entity.id);
+            accounts.extend(entity.accounts.iter_mut());
+            accounts.extend(entity.votes.iter_mut());
+            accounts.extend(
+                entity
+                    .distribution
+                    .iter_mut()
+                    .map(|assignment| &mut assignment.candidate),
+            );
+        }
+        for run in self.balancing.iter_mut().flat_map(|b| b.runs.iter_mut()) {
+            for edge in run.iterations.iter_mut().flat_map(|i| i.edges.iter_mut()) {
+                accounts.push(&mut edge.voter);
+                accounts.push(&mut edge.candidate);
+            }
+        }
+
+        accounts
+    }
+}
+
+/// Comparison of simulated election with the one held on-chain
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiReconciliation {
+    /// Block hash of the election
+    #[serde(rename = "blockHash")]
+    pub block_hash: String,
+    /// Block hash where simulation data was taken from
+    #[serde(rename = "parentBlockHash")]
+    pub parent_block_hash: String,
+    /// Algorithm used for simulation
+    pub algorithm: Algorithm,
+    /// True if all simulated seats and stakes match on-chain ones
+    pub matches: bool,
+    /// Members comparison
+    pub members: Vec<ApiSeatComparison>,
+    /// Runners up comparison
+    #[serde(rename = "runnersUp")]
+    pub runners_up: Vec<ApiSeatComparison>,
+}
+
+/// Simulated and on-chain stake of a seat holder
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiSeatComparison {
+    /// Seat holder account
+    pub id: ApiAccount,
+    /// Stake in simulation (none if not elected in simulation)
+    #[serde(rename = "simulatedStake")]
+    pub simulated_stake: Option<u128>,
+    /// Stake on-chain (none if not elected on-chain)
+    #[serde(rename = "actualStake")]
+    pub actual_stake: Option<u128>,
+    /// Comparison result
+    pub status: ApiSeatStatus,
+}
+
+/// Result of seat comparison
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiSeatStatus {
+    /// Seat and stake match
+    Match,
+    /// Seat matches, but stake differs
+    StakeMismatch,
+    /// Seat taken only in simulation
+    MissingOnChain,
+    /// Seat taken only on-chain
+    MissingInSimulation,
+}
+
+impl ApiReconciliation {
+    pub fn build_from(reconciliation: &ReconciliationOutputs) -> Self {
+        let seats = |seats: &[SeatComparison]| {
+            seats
+                .iter()
+                .map(|seat| ApiSeatComparison {
+                    id: ApiAccount::from(&seat.who),
+                    simulated_stake: seat.simulated_stake,
+                    actual_stake: seat.actual_stake,
+                    status: match (seat.simulated_stake, seat.actual_stake) {
+                        _ if seat.matches() => ApiSeatStatus::Match,
+                        (Some(_), Some(_)) => ApiSeatStatus::StakeMismatch,
+                        (Some(_), None) => ApiSeatStatus::MissingOnChain,
+                        _ => ApiSeatStatus::MissingInSimulation,
+                    },
+                })
+                .collect()
+        };
+
+        Self {
+            block_hash: format!("{:?}", reconciliation.block_hash),
+            parent_block_hash: format!("{:?}", reconciliation.parent_block_hash),
+            algorithm: reconciliation.algorithm,
+            matches: reconciliation.matches(),
+            members: seats(&reconciliation.members),
+            runners_up: seats(&reconciliation.runners_up),
+        }
+    }
+}
+
+/// Election held at the beginning of a council term
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiElectionTerm {
+    /// Block number of the election
+    #[serde(rename = "blockNumber")]
+    pub block_number: u32,
+    /// Block hash of the election
+    #[serde(rename = "blockHash")]
+    pub block_hash: String,
+    /// Block timestamp (milliseconds since Unix epoch)
+    pub timestamp: u64,
+    /// Number of elections commenced so far
+    #[serde(rename = "electionRounds")]
+    pub election_rounds: u32,
+    /// Outcome of the election
+    pub outcome: TermOutcome,
+    /// Elected members with their backing stake
+    pub members: Vec<ApiTermMember>,
+    /// Events affecting seats or candidates during the term
+    pub events: Vec<ApiElectionEvent>,
+}
+
+/// Member elected at the beginning of a term
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiTermMember {
+    /// Member account
+    pub id: ApiAccount,
+    /// Backing stake
+    pub stake: u128,
+}
+
+/// Event affecting council seats or candidates
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiElectionEvent {
+    /// Block number of the event
+    #[serde(rename = "blockNumber")]
+    pub block_number: u32,
+    /// Kind of event
+    pub kind: ElectionEventKind,
+    /// Affected account
+    pub account: ApiAccount,
+    /// Slashed amount
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u128>,
+}
+
+impl From<&ElectionTerm> for ApiElectionTerm {
+    fn from(term: &ElectionTerm) -> Self {
+        Self {
+            block_number: term.block_number,
+            block_hash: format!("{:?}", term.block_hash),
+            timestamp: term.timestamp,
+            election_rounds: term.election_rounds,
+            outcome: term.outcome,
+            members: term
+                .members
+                .iter()
+                .map(|(who, stake)| ApiTermMember {
+                    id: ApiAccount::from(who),
+                    stake: *stake,
+                })
+                .collect(),
+            events: term
+                .events
+                .iter()
+                .map(|event| ApiElectionEvent {
+                    block_number: event.block_number,
+                    kind: event.kind,
+                    account: ApiAccount::from(&event.account),
+                    amount: event.amount,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Network the backend is connected to
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiNetwork {
+    /// Network name
+    pub name: String,
+    /// SS58 address prefix
+    #[serde(rename = "ss58Prefix")]
+    pub ss58_prefix: u16,
+    /// Native token symbol
+    #[serde(rename = "tokenSymbol")]
+    pub token_symbol: String,
+    /// Native token decimals (balances are returned in smallest units)
+    #[serde(rename = "tokenDecimals")]
+    pub token_decimals: u8,
+}
+
+impl From<&NetworkProfile> for ApiNetwork {
+    fn from(profile: &NetworkProfile) -> Self {
+        Self {
+            name: profile.name.to_string(),
+            ss58_prefix: profile.ss58_prefix,
+            token_symbol: profile.token_symbol.to_string(),
+            token_decimals: profile.token_decimals,
+        }
+    }
+}
+
+/// Problem details returned on errors
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiProblem {
+    /// Problem type URI
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Short summary of the problem type
+    pub title: String,
+    /// HTTP status code
+    pub status: u16,
+    /// Explanation specific to this occurrence of the problem
+    pub detail: String,
 }
 
 /// Helper for converting AccountId to ApiAccount
@@ -278,8 +1202,54 @@ impl From<&AccountId> for ApiAccount {
     fn from(account: &AccountId) -> Self {
         // Basic conversion without display name
         Self {
-            address: account.to_string(),
+            address: ss58_address(account),
             display_name: None,
+            current_display_name: None,
+            identity: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(display: Option<&str>, sub_name: Option<&str>) -> ApiIdentity {
+        ApiIdentity {
+            display: display.map(|display| ApiIdentityData::Raw(display.to_string())),
+            parent: sub_name.map(|sub_name| ApiParentAccount {
+                address: ss58_address(&AccountId::from([1; 32])),
+                sub_name: Some(ApiIdentityData::Raw(sub_name.to_string())),
+            }),
+            ..Default::default()
         }
     }
+
+    #[test]
+    fn display_name_of_account() {
+        assert_eq!(
+            identity(Some("Alice"), None).display_name(),
+            Some("Alice".to_string())
+        );
+        assert_eq!(identity(None, None).display_name(), None);
+
+        let hashed = ApiIdentity {
+            display: Some(ApiIdentityData::BlakeTwo256("0x00".to_string())),
+            ..Default::default()
+        };
+        assert_eq!(hashed.display_name(), None);
+    }
+
+    #[test]
+    fn display_name_of_sub_account() {
+        assert_eq!(
+            identity(Some("Alice"), Some("treasury")).display_name(),
+            Some("Alice/treasury".to_string())
+        );
+        // Sub-accounts of parents without display name are named after the sub-account only
+        assert_eq!(
+            identity(None, Some("treasury")).display_name(),
+            Some("treasury".to_string())
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use subxt::ext::codec::{Decode, Encode};
 
 pub mod api;
 
@@ -6,16 +7,109 @@ pub type Hash = <SubstrateConfig as Config>::Hash;
 pub type AccountId = <SubstrateConfig as Config>::AccountId;
 
 /// Elections data downloaded from the chain
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize, Deserialize, Encode, Decode)]
+#[codec(crate = subxt::ext::codec)]
 pub struct OnchainElectionsData {
     pub block_hash: Hash,
     pub desired_members: u32,
     pub desired_runners_up: u32,
     pub election_rounds: u32,
+    pub total_issuance: u128,
     pub members: Vec<SeatHolder<AccountId, u128>>,
     pub runners_up: Vec<SeatHolder<AccountId, u128>>,
     pub candidates: Vec<(AccountId, u128)>,
     pub voting: Vec<(AccountId, Voter<AccountId, u128>)>,
+    #[serde(default)]
+    pub block_number: u32,
+    #[serde(default)]
+    pub timestamp: u64,
+    /// None in snapshots taken before the integrity of `Voting` was recorded
+    pub voting_integrity: Option<VotingIntegrity>,
+}
+
+/// Prefix of SCALE encoded elections data (snapshots and disk cache)
+const ELECTIONS_DATA_MAGIC: &[u8; 4] = b"llel";
+/// Version of SCALE encoded elections data, following the prefix
+pub const ELECTIONS_DATA_VERSION: u8 = 1;
+
+impl OnchainElectionsData {
+    /// SCALE encoding, prefixed with format version
+    pub fn encode_versioned(&self) -> Vec<u8> {
+        let mut bytes = ELECTIONS_DATA_MAGIC.to_vec();
+        bytes.push(ELECTIONS_DATA_VERSION);
+        self.encode_to(&mut bytes);
+
+        bytes
+    }
+
+    /// Decode data encoded with `encode_versioned`, rejecting other format versions
+    pub fn decode_versioned(bytes: &[u8]) -> Result<Self, Error> {
+        let versioned = bytes
+            .strip_prefix(ELECTIONS_DATA_MAGIC)
+            .and_then(|bytes| bytes.split_first());
+        match versioned {
+            Some((&ELECTIONS_DATA_VERSION, mut data)) => {
+                Self::decode(&mut data).map_err(|e| Error::Decode(format!("Elections data: {}", e)))
+            }
+            Some((version, _)) => Err(Error::Decode(format!(
+                "Unsupported elections data version {} (expected {})",
+                version, ELECTIONS_DATA_VERSION
+            ))),
+            None => Err(Error::Decode(
+                "Elections data has no format version (written by an older release)".to_string(),
+            )),
+        }
+    }
+}
+
+/// Integrity record of `Voting` storage enumeration
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize, Encode, Decode)]
+#[codec(crate = subxt::ext::codec)]
+pub struct VotingIntegrity {
+    /// Number of enumerated `Voting` keys, counted independently of decoded voters
+    pub keys: u32,
+    /// Number of enumerated pages
+    pub pages: u32,
+    /// Number of retried requests
+    pub retries: u32,
+}
+
+/// Changes of `Voting` storage in a block
+pub enum VotingChanges {
+    /// Only votes of these accounts may have changed
+    Accounts(Vec<AccountId>),
+    /// Any votes may have changed
+    Unknown,
+}
+
+/// Block used when no block hash is given
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LatestBlock {
+    /// Latest finalized block
+    #[default]
+    Finalized,
+    /// Best (possibly not finalized) block
+    Best,
+}
+
+/// Election algorithm used for simulation
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Algorithm {
+    /// Sequential Phragmen, as used by `pallet_elections_phragmen`
+    #[default]
+    SeqPhragmen,
+    /// PhragMMS
+    Phragmms,
+}
+
+/// Parameters of a single simulation
+#[derive(Clone, Copy, Default)]
+pub struct SimulationParams {
+    pub algorithm: Algorithm,
+    /// Balancing configuration, with tolerance in balance units
+    pub balancing: Option<BalancingConfig>,
 }
 
 /// Intermediate representation used by Phragmen
@@ -23,12 +117,103 @@ pub struct OnchainElectionsData {
 pub struct PhragmenInputs {
     pub to_elect: usize,
     pub candidates: Vec<AccountId>,
-    pub voters: Vec<(AccountId, u64, Vec<AccountId>)>,
+    pub voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+    pub currency_to_vote: CurrencyToVote,
 }
 
 /// Type returned from hacked sp-npos-elections crate
 pub struct PhragmenOutputs {
     pub result: ElectionResult<AccountId, Perbill>,
+    pub staked_assignments: Vec<StakedAssignment<AccountId>>,
     pub candidates: Vec<CandidatePtr<AccountId>>,
     pub traces: Vec<PhragmenTrace<AccountId>>,
+    pub currency_to_vote: CurrencyToVote,
+    pub algorithm: Algorithm,
+    /// Balancing configuration used, with tolerance in vote weight units
+    pub balancing: Option<BalancingConfig>,
+}
+
+/// Result of PJR verification of a simulated election
+pub struct PjrOutputs {
+    pub satisfied: bool,
+    pub threshold: ExtendedBalance,
+    pub counter_example: Option<PjrCounterExample<AccountId>>,
+    pub currency_to_vote: CurrencyToVote,
+    pub algorithm: Algorithm,
+}
+
+/// Comparison of simulated election with the one held on-chain
+pub struct ReconciliationOutputs {
+    pub block_hash: Hash,
+    pub parent_block_hash: Hash,
+    pub algorithm: Algorithm,
+    pub members: Vec<SeatComparison>,
+    pub runners_up: Vec<SeatComparison>,
+}
+
+impl ReconciliationOutputs {
+    /// Whether simulated seats and stakes match on-chain ones
+    pub fn matches(&self) -> bool {
+        self.members
+            .iter()
+            .chain(self.runners_up.iter())
+            .all(SeatComparison::matches)
+    }
+}
+
+/// Simulated and on-chain stake of a seat holder (None if seat wasn't taken)
+#[derive(Clone, Debug)]
+pub struct SeatComparison {
+    pub who: AccountId,
+    pub simulated_stake: Option<u128>,
+    pub actual_stake: Option<u128>,
+}
+
+impl SeatComparison {
+    pub fn matches(&self) -> bool {
+        self.simulated_stake.is_some() && self.simulated_stake == self.actual_stake
+    }
+}
+
+/// Election held at the beginning of a council term
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ElectionTerm {
+    pub block_number: u32,
+    pub block_hash: Hash,
+    pub timestamp: u64,
+    pub election_rounds: u32,
+    pub outcome: TermOutcome,
+    pub members: Vec<(AccountId, u128)>,
+    pub events: Vec<ElectionEvent>,
+}
+
+/// Outcome of an election, as reported by `pallet_elections_phragmen`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TermOutcome {
+    /// New members elected
+    NewTerm,
+    /// Not enough candidates
+    EmptyTerm,
+    /// Election failed
+    ElectionError,
+}
+
+/// Event affecting council seats or candidates during a term
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ElectionEvent {
+    pub block_number: u32,
+    pub kind: ElectionEventKind,
+    pub account: AccountId,
+    pub amount: Option<u128>,
+}
+
+/// Kind of election event
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ElectionEventKind {
+    MemberKicked,
+    Renounced,
+    CandidateSlashed,
+    SeatHolderSlashed,
 }